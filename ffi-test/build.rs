@@ -0,0 +1,25 @@
+fn main() {
+    println!("cargo:rerun-if-changed=../include/stack_stack.h");
+    println!("cargo:rerun-if-changed=header_test.c");
+    println!("cargo:rerun-if-changed=header_test.cpp");
+
+    cc::Build::new()
+        .file("header_test.c")
+        .include("../include")
+        .std("c11")
+        .flag_if_supported("-pedantic")
+        .extra_warnings(true)
+        .warnings_into_errors(true)
+        .compile("header_test_c");
+
+    cc::Build::new()
+        .cpp(true)
+        .cpp_link_stdlib(None)
+        .file("header_test.cpp")
+        .include("../include")
+        .std("c++11")
+        .flag_if_supported("-pedantic")
+        .extra_warnings(true)
+        .warnings_into_errors(true)
+        .compile("header_test_cpp");
+}
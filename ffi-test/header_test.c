@@ -0,0 +1,56 @@
+/*
+ * Includes include/stack_stack.h and calls every function it declares, so
+ * that the declarations are checked against the functions the crate exports.
+ * Built as both C and C++ by build.rs.
+ */
+
+#include "stack_stack.h"
+
+#ifndef HEADER_TEST
+#define HEADER_TEST stack_stack_header_test_c
+#endif
+
+#ifdef __cplusplus
+extern "C"
+#endif
+int HEADER_TEST(void);
+
+/* Returns the line of the failed check */
+#define CHECK(cond) do { if (!(cond)) return __LINE__; } while (0)
+
+#define CHECK_TYPE(T, name) do { \
+    STACK_STACK(T, 4) s; \
+    RawStack_##name *raw = STACK_STACK_RAW(name, &s); \
+    size_t cap = STACK_STACK_CAPACITY(s); \
+    T values[3] = { 6, 2, 8 }; \
+    T out = 0; \
+    s.len = 0; \
+    CHECK(cap == 4); \
+    CHECK((void *)raw->data == (void *)s.data); \
+    CHECK(stack_stack_##name##_push(raw, cap, 1)); \
+    CHECK(stack_stack_##name##_extend(raw, cap, values, 3) == 3); \
+    CHECK(!stack_stack_##name##_push(raw, cap, 3)); \
+    CHECK(stack_stack_##name##_extend(raw, cap, values, 3) == 0); \
+    CHECK(stack_stack_##name##_pop(raw, &out) && out == 8); \
+    CHECK(stack_stack_##name##_insert(raw, cap, 0, 3)); \
+    CHECK(!stack_stack_##name##_insert(raw, cap, 0, 3)); \
+    CHECK(stack_stack_##name##_remove(raw, 1, &out) && out == 1); \
+    CHECK(!stack_stack_##name##_remove(raw, 3, &out)); \
+    CHECK(s.len == 3 && s.data[0] == 3 && s.data[1] == 6 && s.data[2] == 2); \
+    stack_stack_##name##_clear(raw); \
+    CHECK(s.len == 0 && !stack_stack_##name##_pop(raw, &out)); \
+} while (0)
+
+int HEADER_TEST(void) {
+    CHECK_TYPE(uint8_t, u8);
+    CHECK_TYPE(uint16_t, u16);
+    CHECK_TYPE(uint32_t, u32);
+    CHECK_TYPE(uint64_t, u64);
+    CHECK_TYPE(int8_t, i8);
+    CHECK_TYPE(int16_t, i16);
+    CHECK_TYPE(int32_t, i32);
+    CHECK_TYPE(int64_t, i64);
+    CHECK_TYPE(float, f32);
+    CHECK_TYPE(double, f64);
+    return 0;
+}
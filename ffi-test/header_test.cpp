@@ -0,0 +1,4 @@
+/* Builds header_test.c as C++ */
+
+#define HEADER_TEST stack_stack_header_test_cpp
+#include "header_test.c"
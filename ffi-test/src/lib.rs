@@ -0,0 +1,21 @@
+//!
+//! Checks that `include/stack_stack.h` matches the functions exported by the
+//! `ffi` feature of stack-stack
+//!
+//! The build script compiles a C file, and the same file as C++, that include
+//! the header and call every function it declares. The tests run them linked
+//! against the crate.
+//!
+
+use core::ffi::c_int;
+
+//link the crate so that its exported functions are available
+use stack_stack as _;
+
+extern "C" {
+    /// Runs the checks compiled as C, returning the line of the first failure or `0`
+    pub fn stack_stack_header_test_c() -> c_int;
+
+    /// Runs the checks compiled as C++, returning the line of the first failure or `0`
+    pub fn stack_stack_header_test_cpp() -> c_int;
+}
@@ -0,0 +1,11 @@
+use stack_stack_ffi_test::*;
+
+#[test]
+fn header_as_c() {
+    assert_eq!(unsafe { stack_stack_header_test_c() }, 0, "check failed at this line of header_test.c");
+}
+
+#[test]
+fn header_as_cpp() {
+    assert_eq!(unsafe { stack_stack_header_test_cpp() }, 0, "check failed at this line of header_test.c, as built by header_test.cpp");
+}
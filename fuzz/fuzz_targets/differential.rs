@@ -0,0 +1,89 @@
+//!
+//! Runs random sequences of operations on a `Stack` and a `Vec` and checks
+//! that they behave the same
+//!
+//! Run with `cargo fuzz run differential` from the root of the repository.
+//!
+
+#![no_main]
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use stack_stack::Stack;
+
+const N: usize = 16;
+
+#[derive(Debug)]
+enum Op {
+    Push(u8),
+    Pop,
+    Insert(usize, u8),
+    Remove(usize),
+    SwapRemove(usize),
+    Truncate(usize),
+    Resize(usize, u8),
+}
+
+impl<'a> Arbitrary<'a> for Op {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=6)? {
+            0 => Op::Push(u.arbitrary()?),
+            1 => Op::Pop,
+            2 => Op::Insert(u.arbitrary()?, u.arbitrary()?),
+            3 => Op::Remove(u.arbitrary()?),
+            4 => Op::SwapRemove(u.arbitrary()?),
+            5 => Op::Truncate(u.arbitrary()?),
+            _ => Op::Resize(u.arbitrary()?, u.arbitrary()?),
+        })
+    }
+}
+
+fn run(mut stack: Stack<u8, N>, ops: Vec<Op>) {
+    let mut model = stack.to_vec();
+
+    for op in ops {
+        match op {
+            Op::Push(x) => {
+                if model.len() < N {
+                    model.push(x);
+                    assert_eq!(stack.push(x), Ok(()));
+                } else {
+                    assert_eq!(stack.push(x), Err(x));
+                }
+            },
+            Op::Pop => assert_eq!(stack.pop(), model.pop()),
+            Op::Insert(i, x) => {
+                let i = i % (model.len() + 1);
+                model.insert(i, x);
+                let overflow = if model.len() > N { model.pop() } else { None };
+                assert_eq!(stack.insert(i, x), overflow);
+            },
+            Op::Remove(i) if !model.is_empty() => {
+                let i = i % model.len();
+                assert_eq!(stack.remove(i), model.remove(i));
+            },
+            Op::SwapRemove(i) if !model.is_empty() => {
+                let i = i % model.len();
+                assert_eq!(stack.swap_remove(i), model.swap_remove(i));
+            },
+            Op::Remove(_) | Op::SwapRemove(_) => {},
+            Op::Truncate(len) => {
+                let len = len % (N + 2);
+                model.truncate(len);
+                stack.truncate(len);
+            },
+            Op::Resize(len, x) => {
+                let len = len % (N + 1);
+                model.resize(len, x);
+                stack.resize(len, x);
+            },
+        }
+        assert_eq!(stack.as_slice(), &model[..]);
+        assert_eq!(stack.len(), model.len());
+    }
+}
+
+fuzz_target!(|input: (Stack<u8, N>, Vec<Op>)| {
+    let (stack, ops) = input;
+    run(stack, ops);
+});
@@ -0,0 +1,167 @@
+/*
+ * C declarations for the `ffi` feature of the stack-stack crate
+ *
+ * A stack of capacity N holding values of type T has the layout
+ * `struct { size_t len; T data[N]; }`, the same as `Stack<T, N>` in Rust.
+ * Declare one with STACK_STACK(T, N) and pass it to the functions for its
+ * element type as STACK_STACK_RAW(u8, &stack), along with its capacity.
+ *
+ * Only the first `len` values of `data` are initialized.
+ */
+
+#ifndef STACK_STACK_H
+#define STACK_STACK_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Declares a stack struct of `T` with capacity `N` */
+#define STACK_STACK(T, N) struct { size_t len; T data[N]; }
+
+/* Casts a pointer to a stack of `T` into a `RawStack_{T}` pointer, such as `RawStack_u8 *` */
+#define STACK_STACK_RAW(T, stack) ((RawStack_##T *)(stack))
+
+/* The capacity of a stack declared with STACK_STACK() */
+#define STACK_STACK_CAPACITY(stack) (sizeof((stack).data) / sizeof((stack).data[0]))
+
+/*
+ * The length of the buffer of the RawStack_{T} structs. C++ has no flexible
+ * array members, so a single item stands in for one there. The structs are
+ * only ever used through pointers, so their size doesn't matter.
+ */
+#ifdef __cplusplus
+#define STACK_STACK_FLEXIBLE 1
+#else
+#define STACK_STACK_FLEXIBLE
+#endif
+
+typedef struct {
+  size_t len;
+  uint8_t data[STACK_STACK_FLEXIBLE];
+} RawStack_u8;
+
+bool stack_stack_u8_push(RawStack_u8 *stack, size_t capacity, uint8_t value);
+bool stack_stack_u8_pop(RawStack_u8 *stack, uint8_t *out);
+bool stack_stack_u8_insert(RawStack_u8 *stack, size_t capacity, size_t index, uint8_t value);
+bool stack_stack_u8_remove(RawStack_u8 *stack, size_t index, uint8_t *out);
+size_t stack_stack_u8_extend(RawStack_u8 *stack, size_t capacity, const uint8_t *values, size_t count);
+void stack_stack_u8_clear(RawStack_u8 *stack);
+
+typedef struct {
+  size_t len;
+  uint16_t data[STACK_STACK_FLEXIBLE];
+} RawStack_u16;
+
+bool stack_stack_u16_push(RawStack_u16 *stack, size_t capacity, uint16_t value);
+bool stack_stack_u16_pop(RawStack_u16 *stack, uint16_t *out);
+bool stack_stack_u16_insert(RawStack_u16 *stack, size_t capacity, size_t index, uint16_t value);
+bool stack_stack_u16_remove(RawStack_u16 *stack, size_t index, uint16_t *out);
+size_t stack_stack_u16_extend(RawStack_u16 *stack, size_t capacity, const uint16_t *values, size_t count);
+void stack_stack_u16_clear(RawStack_u16 *stack);
+
+typedef struct {
+  size_t len;
+  uint32_t data[STACK_STACK_FLEXIBLE];
+} RawStack_u32;
+
+bool stack_stack_u32_push(RawStack_u32 *stack, size_t capacity, uint32_t value);
+bool stack_stack_u32_pop(RawStack_u32 *stack, uint32_t *out);
+bool stack_stack_u32_insert(RawStack_u32 *stack, size_t capacity, size_t index, uint32_t value);
+bool stack_stack_u32_remove(RawStack_u32 *stack, size_t index, uint32_t *out);
+size_t stack_stack_u32_extend(RawStack_u32 *stack, size_t capacity, const uint32_t *values, size_t count);
+void stack_stack_u32_clear(RawStack_u32 *stack);
+
+typedef struct {
+  size_t len;
+  uint64_t data[STACK_STACK_FLEXIBLE];
+} RawStack_u64;
+
+bool stack_stack_u64_push(RawStack_u64 *stack, size_t capacity, uint64_t value);
+bool stack_stack_u64_pop(RawStack_u64 *stack, uint64_t *out);
+bool stack_stack_u64_insert(RawStack_u64 *stack, size_t capacity, size_t index, uint64_t value);
+bool stack_stack_u64_remove(RawStack_u64 *stack, size_t index, uint64_t *out);
+size_t stack_stack_u64_extend(RawStack_u64 *stack, size_t capacity, const uint64_t *values, size_t count);
+void stack_stack_u64_clear(RawStack_u64 *stack);
+
+typedef struct {
+  size_t len;
+  int8_t data[STACK_STACK_FLEXIBLE];
+} RawStack_i8;
+
+bool stack_stack_i8_push(RawStack_i8 *stack, size_t capacity, int8_t value);
+bool stack_stack_i8_pop(RawStack_i8 *stack, int8_t *out);
+bool stack_stack_i8_insert(RawStack_i8 *stack, size_t capacity, size_t index, int8_t value);
+bool stack_stack_i8_remove(RawStack_i8 *stack, size_t index, int8_t *out);
+size_t stack_stack_i8_extend(RawStack_i8 *stack, size_t capacity, const int8_t *values, size_t count);
+void stack_stack_i8_clear(RawStack_i8 *stack);
+
+typedef struct {
+  size_t len;
+  int16_t data[STACK_STACK_FLEXIBLE];
+} RawStack_i16;
+
+bool stack_stack_i16_push(RawStack_i16 *stack, size_t capacity, int16_t value);
+bool stack_stack_i16_pop(RawStack_i16 *stack, int16_t *out);
+bool stack_stack_i16_insert(RawStack_i16 *stack, size_t capacity, size_t index, int16_t value);
+bool stack_stack_i16_remove(RawStack_i16 *stack, size_t index, int16_t *out);
+size_t stack_stack_i16_extend(RawStack_i16 *stack, size_t capacity, const int16_t *values, size_t count);
+void stack_stack_i16_clear(RawStack_i16 *stack);
+
+typedef struct {
+  size_t len;
+  int32_t data[STACK_STACK_FLEXIBLE];
+} RawStack_i32;
+
+bool stack_stack_i32_push(RawStack_i32 *stack, size_t capacity, int32_t value);
+bool stack_stack_i32_pop(RawStack_i32 *stack, int32_t *out);
+bool stack_stack_i32_insert(RawStack_i32 *stack, size_t capacity, size_t index, int32_t value);
+bool stack_stack_i32_remove(RawStack_i32 *stack, size_t index, int32_t *out);
+size_t stack_stack_i32_extend(RawStack_i32 *stack, size_t capacity, const int32_t *values, size_t count);
+void stack_stack_i32_clear(RawStack_i32 *stack);
+
+typedef struct {
+  size_t len;
+  int64_t data[STACK_STACK_FLEXIBLE];
+} RawStack_i64;
+
+bool stack_stack_i64_push(RawStack_i64 *stack, size_t capacity, int64_t value);
+bool stack_stack_i64_pop(RawStack_i64 *stack, int64_t *out);
+bool stack_stack_i64_insert(RawStack_i64 *stack, size_t capacity, size_t index, int64_t value);
+bool stack_stack_i64_remove(RawStack_i64 *stack, size_t index, int64_t *out);
+size_t stack_stack_i64_extend(RawStack_i64 *stack, size_t capacity, const int64_t *values, size_t count);
+void stack_stack_i64_clear(RawStack_i64 *stack);
+
+typedef struct {
+  size_t len;
+  float data[STACK_STACK_FLEXIBLE];
+} RawStack_f32;
+
+bool stack_stack_f32_push(RawStack_f32 *stack, size_t capacity, float value);
+bool stack_stack_f32_pop(RawStack_f32 *stack, float *out);
+bool stack_stack_f32_insert(RawStack_f32 *stack, size_t capacity, size_t index, float value);
+bool stack_stack_f32_remove(RawStack_f32 *stack, size_t index, float *out);
+size_t stack_stack_f32_extend(RawStack_f32 *stack, size_t capacity, const float *values, size_t count);
+void stack_stack_f32_clear(RawStack_f32 *stack);
+
+typedef struct {
+  size_t len;
+  double data[STACK_STACK_FLEXIBLE];
+} RawStack_f64;
+
+bool stack_stack_f64_push(RawStack_f64 *stack, size_t capacity, double value);
+bool stack_stack_f64_pop(RawStack_f64 *stack, double *out);
+bool stack_stack_f64_insert(RawStack_f64 *stack, size_t capacity, size_t index, double value);
+bool stack_stack_f64_remove(RawStack_f64 *stack, size_t index, double *out);
+size_t stack_stack_f64_extend(RawStack_f64 *stack, size_t capacity, const double *values, size_t count);
+void stack_stack_f64_clear(RawStack_f64 *stack);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif /* STACK_STACK_H */
@@ -0,0 +1,179 @@
+use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use core::ops::Deref;
+
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor::{fail, Fallible, Source};
+use rkyv::ser::{Allocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::{Archive, Deserialize, Place, Portable, Serialize};
+
+use crate::{LenType, Stack};
+
+///
+/// The archived form of a [`Stack`], with the `rkyv` feature
+///
+/// The values are stored as a relative slice, like an archived `Vec`, so an
+/// archived stack only takes as much space as its values. Validation also
+/// checks that there are no more than `N` values, so it can always be
+/// deserialized back into a [`Stack`]. It dereferences to a slice of the
+/// archived values, so it can be read in place.
+///
+/// Enabling `rkyv` also brings in `rend`, which implements `PartialEq`
+/// between its endian-aware integers and the primitive ones. This can make
+/// comparisons with untyped literals ambiguous anywhere in the build, such
+/// as `assert_eq!(stack, [])` for a stack of `i32`, so those need their type
+/// spelled out, e.g. `[] as [i32; 0]`.
+///
+/// # Examples
+/// This uses rkyv's allocation-free API, which works without its `alloc`
+/// feature
+/// ```
+/// # use stack_stack::{Stack, stack, ArchivedStack};
+/// use core::mem::MaybeUninit;
+/// use rkyv::api::low::{access, deserialize, to_bytes_in_with_alloc};
+/// use rkyv::rancor::Failure;
+/// use rkyv::ser::{allocator::SubAllocator, writer::Buffer};
+/// use rkyv::util::Align;
+/// use rkyv::Archived;
+///
+/// let mut output = Align([MaybeUninit::<u8>::uninit(); 64]);
+/// let mut scratch = [MaybeUninit::<u8>::uninit(); 64];
+///
+/// let s: Stack<u32, 8> = stack![6, 2, 8; 8];
+/// let bytes = to_bytes_in_with_alloc::<_, _, Failure>(
+///     &s, Buffer::from(&mut *output), SubAllocator::new(&mut scratch)
+/// ).unwrap();
+///
+/// let archived = access::<ArchivedStack<Archived<u32>, 8>, Failure>(&bytes).unwrap();
+/// assert_eq!(archived.len(), 3);
+/// assert_eq!(archived[1], 2);
+///
+/// let restored: Stack<u32, 8> = deserialize::<_, Failure>(archived).unwrap();
+/// assert_eq!(restored, s);
+///
+/// //values archived from a larger stack don't validate as a smaller one
+/// let big: Stack<u32, 8> = stack![0; 5; 8];
+/// let bytes = to_bytes_in_with_alloc::<_, _, Failure>(
+///     &big, Buffer::from(&mut *output), SubAllocator::new(&mut scratch)
+/// ).unwrap();
+/// assert!(access::<ArchivedStack<Archived<u32>, 4>, Failure>(&bytes).is_err());
+/// ```
+///
+#[repr(transparent)]
+pub struct ArchivedStack<T, const N:usize> {
+    values: ArchivedVec<T>
+}
+
+//SAFETY: `ArchivedVec` is portable and this is a transparent wrapper around it
+unsafe impl<T:Portable, const N:usize> Portable for ArchivedStack<T,N> {}
+
+impl<T, const N:usize> ArchivedStack<T,N> {
+
+    /// The quantity of values in the stack
+    pub fn len(&self) -> usize { self.values.len() }
+
+    /// `true` when the stack contains no values
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+
+    /// The capacity of the stack the values were archived from
+    pub fn capacity(&self) -> usize { N }
+
+    /// Returns a slice of the archived values
+    pub fn as_slice(&self) -> &[T] { self.values.as_slice() }
+
+}
+
+impl<T, const N:usize> Deref for ArchivedStack<T,N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] { self.as_slice() }
+}
+
+impl<T, const N:usize> AsRef<[T]> for ArchivedStack<T,N> {
+    fn as_ref(&self) -> &[T] { self.as_slice() }
+}
+
+impl<T:Debug, const N:usize> Debug for ArchivedStack<T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T:PartialEq<U>, U, const N:usize, const M:usize, L:LenType> PartialEq<Stack<U,M,L>> for ArchivedStack<T,N> {
+    fn eq(&self, other: &Stack<U,M,L>) -> bool { self.as_slice().eq(other.as_slice()) }
+}
+
+impl<T:PartialEq<U>, U, const N:usize> PartialEq<[U]> for ArchivedStack<T,N> {
+    fn eq(&self, other: &[U]) -> bool { self.as_slice().eq(other) }
+}
+
+/// Error reported when an archived stack has more values than its capacity
+#[derive(Debug)]
+struct CapacityExceeded {
+    len: usize,
+    capacity: usize
+}
+
+impl Display for CapacityExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "archived stack has {} values, but the capacity is {}", self.len, self.capacity)
+    }
+}
+
+impl core::error::Error for CapacityExceeded {}
+
+unsafe impl<T, C, const N:usize> CheckBytes<C> for ArchivedStack<T,N>
+where
+    ArchivedVec<T>: CheckBytes<C>,
+    C: Fallible + ?Sized,
+    C::Error: Source
+{
+    unsafe fn check_bytes(value: *const Self, context: &mut C) -> Result<(),C::Error> {
+        //the length is a plain integer, so it's valid to read before the rest is checked
+        let values = value as *const ArchivedVec<T>;
+        let len = (*values).len();
+        if len > N {
+            fail!(CapacityExceeded { len, capacity: N });
+        }
+        ArchivedVec::check_bytes(values, context)
+    }
+}
+
+impl<T:Archive, const N:usize, L:LenType> Archive for Stack<T,N,L> {
+    type Archived = ArchivedStack<T::Archived, N>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: VecResolver, out: Place<Self::Archived>) {
+        //SAFETY: `ArchivedStack` is a transparent wrapper around `ArchivedVec`
+        let out = unsafe { out.cast_unchecked::<ArchivedVec<T::Archived>>() };
+        ArchivedVec::resolve_from_slice(self.as_slice(), resolver, out);
+    }
+}
+
+impl<T, S, const N:usize, L:LenType> Serialize<S> for Stack<T,N,L>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized
+{
+    fn serialize(&self, serializer: &mut S) -> Result<VecResolver,S::Error> {
+        ArchivedVec::serialize_from_slice(self.as_slice(), serializer)
+    }
+}
+
+impl<T, D, const N:usize, L:LenType> Deserialize<Stack<T,N,L>, D> for ArchivedStack<T::Archived, N>
+where
+    T: Archive,
+    T::Archived: Deserialize<T,D>,
+    D: Fallible + ?Sized,
+    D::Error: Source
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<Stack<T,N,L>,D::Error> {
+        if self.len() > N {
+            fail!(CapacityExceeded { len: self.len(), capacity: N });
+        }
+        let mut stack = Stack::new();
+        for value in self.as_slice() {
+            stack.push(value.deserialize(deserializer)?).ok();
+        }
+        Ok(stack)
+    }
+}
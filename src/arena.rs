@@ -0,0 +1,356 @@
+//!
+//! A bump allocator backed by an inline byte array
+//!
+//! Like [`Stack`](crate::Stack), a [`StackArena`] never touches the heap.
+//! It hands out memory from its buffer in order, and reclaims it all at once
+//! with [`StackArena::reset()`] or back to an [`ArenaCheckpoint`] with
+//! [`StackArena::rewind()`].
+//!
+//! The arena can also be used as a [`GlobalAlloc`] or, with the
+//! `allocator-api2` feature, as an `allocator_api2::alloc::Allocator` so
+//! that collections can be built inside a bounded scratch region.
+//!
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use core::mem::MaybeUninit;
+use core::ptr::{self, NonNull};
+use core::slice::from_raw_parts_mut;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+///
+/// A bump allocator storing its allocations in an inline buffer of `BYTES` bytes
+///
+/// Allocating only requires a shared reference, so many values can be
+/// allocated and borrowed at the same time. Reclaiming memory requires a
+/// mutable reference, which guarrantees that nothing allocated from the
+/// reclaimed region is still borrowed.
+///
+/// Values allocated in the arena are **never** dropped. Their memory is
+/// simply reused once the arena is reset or rewound.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::StackArena;
+/// let mut arena = StackArena::<64>::new();
+///
+/// let x = arena.alloc(6u32).unwrap();
+/// let s = arena.alloc_slice_copy(&[2u8, 8, 3]).unwrap();
+/// *x += 1;
+/// s[0] = 1;
+///
+/// assert_eq!(*x, 7);
+/// assert_eq!(s, &[1, 8, 3]);
+///
+/// //over-capacity allocations hand the value back
+/// assert_eq!(arena.alloc([0u8; 64]), Err([0u8; 64]));
+///
+/// arena.reset();
+/// assert_eq!(arena.used(), 0);
+/// ```
+///
+pub struct StackArena<const BYTES:usize> {
+    used: AtomicUsize,
+    data: UnsafeCell<[MaybeUninit<u8>; BYTES]>
+}
+
+unsafe impl<const BYTES:usize> Sync for StackArena<BYTES> {}
+
+impl<const BYTES:usize> Default for StackArena<BYTES> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<const BYTES:usize> Debug for StackArena<BYTES> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("StackArena")
+            .field("used", &self.used())
+            .field("capacity", &BYTES)
+            .finish()
+    }
+}
+
+///
+/// A saved position within a [`StackArena`]
+///
+/// Created with [`StackArena::checkpoint()`] and consumed by
+/// [`StackArena::rewind()`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArenaCheckpoint {
+    used: usize
+}
+
+impl<const BYTES:usize> StackArena<BYTES> {
+
+    ///
+    /// Creates an empty arena
+    ///
+    /// Since this is `const`, an arena can be stored in a `static` and even be
+    /// used as the `#[global_allocator]`
+    ///
+    pub const fn new() -> Self {
+        Self {
+            used: AtomicUsize::new(0),
+            data: UnsafeCell::new(unsafe { MaybeUninit::uninit().assume_init() })
+        }
+    }
+
+    /// The quantity of bytes allocated so far, including any alignment padding
+    pub fn used(&self) -> usize { self.used.load(Ordering::Relaxed) }
+
+    /// The quantity of bytes left in the arena, ignoring alignment
+    pub fn remaining(&self) -> usize { BYTES - self.used() }
+
+    /// The total size of the arena in bytes. Equivalent to `BYTES`
+    pub const fn capacity(&self) -> usize { BYTES }
+
+    fn base(&self) -> *mut u8 { self.data.get() as *mut u8 }
+
+    ///
+    /// Allocates a block of memory fitting the given layout
+    ///
+    /// Returns `None` if there is not enough room left in the arena
+    ///
+    pub fn alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let base = self.base();
+        //acquire the bytes that other threads gave back before reusing them
+        let mut used = self.used.load(Ordering::Acquire);
+        loop {
+            let addr = (base as usize).checked_add(used)?.checked_add(layout.align() - 1)?;
+            let start = (addr & !(layout.align() - 1)) - base as usize;
+            let end = start.checked_add(layout.size()).filter(|end| *end <= BYTES)?;
+            match self.used.compare_exchange_weak(used, end, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return NonNull::new(unsafe { base.add(start) }),
+                Err(current) => used = current
+            }
+        }
+    }
+
+    ///
+    /// Moves a value into the arena and returns a reference to it
+    ///
+    /// If there is not enough room left, the value is returned in an `Err()`
+    ///
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, x:T) -> Result<&mut T, T> {
+        match self.alloc_layout(Layout::new::<T>()) {
+            Some(ptr) => unsafe {
+                let ptr = ptr.as_ptr() as *mut T;
+                ptr.write(x);
+                Ok(&mut *ptr)
+            },
+            None => Err(x)
+        }
+    }
+
+    ///
+    /// Allocates a slice of `len` values, each created by calling `f` with
+    /// its index
+    ///
+    /// Returns `None` if there is not enough room left in the arena
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::StackArena;
+    /// let arena = StackArena::<64>::new();
+    /// let squares = arena.alloc_slice_fill_with(5, |i| i*i).unwrap();
+    /// assert_eq!(squares, &[0, 1, 4, 9, 16]);
+    /// ```
+    ///
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_fill_with<T, F:FnMut(usize)->T>(&self, len: usize, mut f:F) -> Option<&mut [T]> {
+        let ptr = self.alloc_layout(Layout::array::<T>(len).ok()?)?.as_ptr() as *mut T;
+        for i in 0..len {
+            //if `f` panics, the values written so far are simply leaked
+            unsafe { ptr.add(i).write(f(i)); }
+        }
+        unsafe { Some(from_raw_parts_mut(ptr, len)) }
+    }
+
+    ///
+    /// Allocates a copy of a slice
+    ///
+    /// Returns `None` if there is not enough room left in the arena
+    ///
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T:Copy>(&self, slice: &[T]) -> Option<&mut [T]> {
+        let ptr = self.alloc_layout(Layout::for_value(slice))?.as_ptr() as *mut T;
+        unsafe {
+            ptr::copy_nonoverlapping(slice.as_ptr(), ptr, slice.len());
+            Some(from_raw_parts_mut(ptr, slice.len()))
+        }
+    }
+
+    ///
+    /// Allocates a slice of clones of the values in another slice
+    ///
+    /// Returns `None` if there is not enough room left in the arena
+    ///
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_clone<T:Clone>(&self, slice: &[T]) -> Option<&mut [T]> {
+        self.alloc_slice_fill_with(slice.len(), |i| slice[i].clone())
+    }
+
+    ///
+    /// Frees every allocation in the arena at once
+    ///
+    /// Nothing is dropped. The memory is only made available again.
+    ///
+    pub fn reset(&mut self) {
+        *self.used.get_mut() = 0;
+    }
+
+    ///
+    /// Saves the current position of the arena so that it can be later
+    /// returned to with [`Self::rewind()`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::StackArena;
+    /// let mut arena = StackArena::<64>::new();
+    /// arena.alloc(6u64).unwrap();
+    ///
+    /// let checkpoint = arena.checkpoint();
+    /// arena.alloc([2u64, 8, 3]).unwrap();
+    /// assert_ne!(arena.checkpoint(), checkpoint);
+    ///
+    /// arena.rewind(checkpoint);
+    /// assert_eq!(arena.checkpoint(), checkpoint);
+    /// ```
+    ///
+    pub fn checkpoint(&self) -> ArenaCheckpoint {
+        ArenaCheckpoint { used: self.used() }
+    }
+
+    ///
+    /// Frees every allocation made since an [`ArenaCheckpoint`] was taken
+    ///
+    /// If the arena was already rewound or reset past the checkpoint, this
+    /// does nothing
+    ///
+    pub fn rewind(&mut self, checkpoint: ArenaCheckpoint) {
+        let used = self.used.get_mut();
+        *used = checkpoint.used.min(*used);
+    }
+
+    ///
+    /// Gives back the memory of an allocation if it is the most recent one
+    ///
+    /// Returns `true` if the memory was reclaimed
+    ///
+    fn release(&self, ptr: *mut u8, size: usize) -> bool {
+        let start = ptr as usize - self.base() as usize;
+        self.used.compare_exchange(start + size, start, Ordering::AcqRel, Ordering::Acquire).is_ok()
+    }
+
+    ///
+    /// Resizes an allocation in place if it is the most recent one
+    ///
+    fn resize_in_place(&self, ptr: *mut u8, old_size: usize, new_size: usize) -> bool {
+        let start = ptr as usize - self.base() as usize;
+        start + new_size <= BYTES && self.used.compare_exchange(
+            start + old_size, start + new_size, Ordering::AcqRel, Ordering::Acquire
+        ).is_ok()
+    }
+
+}
+
+///
+/// Allows an arena to be used as the global allocator
+///
+/// Deallocating only reclaims memory for the most recent allocation.
+///
+/// ```no_run
+/// # use stack_stack::StackArena;
+/// #[global_allocator]
+/// static HEAP: StackArena<65536> = StackArena::new();
+/// ```
+///
+unsafe impl<const BYTES:usize> GlobalAlloc for StackArena<BYTES> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.alloc_layout(layout).map_or(ptr::null_mut(), NonNull::as_ptr)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.release(ptr, layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if self.resize_in_place(ptr, layout.size(), new_size) { return ptr; }
+        match self.alloc_layout(Layout::from_size_align_unchecked(new_size, layout.align())) {
+            Some(new) => {
+                ptr::copy_nonoverlapping(ptr, new.as_ptr(), layout.size().min(new_size));
+                new.as_ptr()
+            },
+            None => ptr::null_mut()
+        }
+    }
+}
+
+///
+/// Allows collections from `allocator_api2` to allocate in the arena
+///
+/// # Examples
+/// Collections like `allocator_api2::vec::Vec` need its `alloc` feature, but
+/// the arena can also be used through the trait directly
+/// ```
+/// # use stack_stack::StackArena;
+/// use allocator_api2::alloc::Allocator;
+/// use core::alloc::Layout;
+///
+/// let arena = StackArena::<256>::new();
+/// let layout = Layout::array::<u32>(4).unwrap();
+///
+/// let block = (&arena).allocate(layout).unwrap();
+/// assert_eq!(block.len(), 16);
+/// assert!(arena.used() >= 16);
+///
+/// //the last block grows in place
+/// let bigger = Layout::array::<u32>(8).unwrap();
+/// let grown = unsafe { (&arena).grow(block.cast(), layout, bigger) }.unwrap();
+/// assert_eq!(grown.cast::<u8>(), block.cast::<u8>());
+///
+/// //and too large a block is refused rather than panicking
+/// assert!((&arena).allocate(Layout::array::<u32>(100).unwrap()).is_err());
+///
+/// unsafe { (&arena).deallocate(grown.cast(), bigger) };
+/// ```
+///
+#[cfg(feature = "allocator-api2")]
+unsafe impl<const BYTES:usize> allocator_api2::alloc::Allocator for &StackArena<BYTES> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, allocator_api2::alloc::AllocError> {
+        self.alloc_layout(layout)
+            .map(|ptr| NonNull::slice_from_raw_parts(ptr, layout.size()))
+            .ok_or(allocator_api2::alloc::AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.release(ptr.as_ptr(), layout.size());
+    }
+
+    unsafe fn grow(
+        &self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout
+    ) -> Result<NonNull<[u8]>, allocator_api2::alloc::AllocError> {
+        if ptr.as_ptr().align_offset(new_layout.align()) == 0
+            && self.resize_in_place(ptr.as_ptr(), old_layout.size(), new_layout.size())
+        {
+            return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+        }
+        let new = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new.as_ptr() as *mut u8, old_layout.size());
+        Ok(new)
+    }
+
+    unsafe fn shrink(
+        &self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout
+    ) -> Result<NonNull<[u8]>, allocator_api2::alloc::AllocError> {
+        if ptr.as_ptr().align_offset(new_layout.align()) == 0 {
+            self.resize_in_place(ptr.as_ptr(), old_layout.size(), new_layout.size());
+            return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+        }
+        let new = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new.as_ptr() as *mut u8, new_layout.size());
+        Ok(new)
+    }
+}
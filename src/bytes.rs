@@ -0,0 +1,500 @@
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{LenType, Stack, StackBase, Storage};
+
+///
+/// A [`Stack`] of bytes, with methods for encoding values into it
+///
+/// Every `put_*` method either writes the whole value or, if it doesn't fit,
+/// returns a [`CapacityError`] and leaves the stack unchanged. The bytes can
+/// be read back with a [`ByteReader`]. Types can define their own encoding
+/// by implementing [`Encode`] and [`Decode`].
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{ByteStack, CapacityError};
+/// let mut buf: ByteStack<8> = ByteStack::new();
+/// buf.put_u16_be(0x0102).unwrap();
+/// buf.put_varint(300).unwrap();
+/// assert_eq!(buf, [0x01, 0x02, 0xac, 0x02]);
+///
+/// assert_eq!(buf.put_u64_le(0), Err(CapacityError));
+/// assert_eq!(buf.len(), 4);
+///
+/// let mut reader = buf.reader();
+/// assert_eq!(reader.get_u16_be(), Ok(0x0102));
+/// assert_eq!(reader.get_varint(), Ok(300));
+/// assert!(reader.is_empty());
+/// ```
+///
+pub type ByteStack<const N:usize, L = usize> = Stack<u8,N,L>;
+
+///
+/// Error returned when a value doesn't fit in the remaining capacity of a
+/// [`ByteStack`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapacityError;
+
+impl Display for CapacityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("not enough capacity left for the value")
+    }
+}
+
+///
+/// Error returned when a [`ByteReader`] can't decode a value
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeError {
+    /// The bytes ended before the value did
+    UnexpectedEnd,
+    /// A varint was longer than its type allows
+    InvalidVarint,
+    /// A decoded sequence had more items than the capacity of its stack
+    Overflow,
+    /// The bytes were not a valid value of the type
+    Invalid
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of bytes"),
+            Self::InvalidVarint => f.write_str("varint too long for its type"),
+            Self::Overflow => f.write_str("too many items for the capacity of the stack"),
+            Self::Invalid => f.write_str("invalid value"),
+        }
+    }
+}
+
+///
+/// A type that can be written into a [`ByteStack`]
+///
+/// Implemented for the primitive integers and floats, which are written in
+/// little-endian, for `bool`, and for stacks of encodable values, which are
+/// prefixed with their length as a varint.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{ByteStack, StackBase, Storage, LenType, Encode, Decode, ByteReader, CapacityError, DecodeError};
+/// #[derive(Debug, PartialEq)]
+/// struct Point { x: i32, y: i32 }
+///
+/// impl Encode for Point {
+///     fn encode<S:?Sized+Storage<u8>, L:LenType>(&self, buf: &mut StackBase<u8,S,L>) -> Result<(),CapacityError> {
+///         buf.put_varint_signed(self.x.into())?;
+///         buf.put_varint_signed(self.y.into())
+///     }
+/// }
+///
+/// impl Decode for Point {
+///     fn decode(reader: &mut ByteReader<'_>) -> Result<Self,DecodeError> {
+///         let x = reader.get_varint_signed()?.try_into().map_err(|_| DecodeError::Invalid)?;
+///         let y = reader.get_varint_signed()?.try_into().map_err(|_| DecodeError::Invalid)?;
+///         Ok(Point { x, y })
+///     }
+/// }
+///
+/// let mut buf: ByteStack<4> = ByteStack::new();
+/// buf.put_value(&Point { x: 6, y: -2 }).unwrap();
+/// assert_eq!(buf.put_value(&Point { x: 1000, y: 1000 }), Err(CapacityError));
+/// assert_eq!(buf, [0x06, 0x7e]);
+///
+/// assert_eq!(buf.reader().get_value(), Ok(Point { x: 6, y: -2 }));
+/// ```
+///
+pub trait Encode {
+    ///
+    /// Writes the value to the end of the stack
+    ///
+    /// This may leave part of the value written when it returns an error.
+    /// [`StackBase::put_value()`] removes it again.
+    ///
+    fn encode<S:?Sized+Storage<u8>, L:LenType>(&self, buf: &mut StackBase<u8,S,L>) -> Result<(),CapacityError>;
+}
+
+///
+/// A type that can be read from a [`ByteReader`]. See [`Encode`]
+///
+pub trait Decode: Sized {
+    ///
+    /// Reads a value from the reader
+    ///
+    /// This may leave the reader partway through the value when it returns
+    /// an error. [`ByteReader::get_value()`] moves it back again.
+    ///
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self,DecodeError>;
+}
+
+macro_rules! put_methods {
+    ($($ty:ty => $le:ident, $be:ident;)*) => {$(
+        #[doc = concat!("Writes a `", stringify!($ty), "` in little-endian")]
+        pub fn $le(&mut self, x: $ty) -> Result<(),CapacityError> {
+            self.put_slice(&x.to_le_bytes())
+        }
+
+        #[doc = concat!("Writes a `", stringify!($ty), "` in big-endian")]
+        pub fn $be(&mut self, x: $ty) -> Result<(),CapacityError> {
+            self.put_slice(&x.to_be_bytes())
+        }
+    )*};
+}
+
+impl<S:?Sized+Storage<u8>, L:LenType> StackBase<u8,S,L> {
+
+    ///
+    /// Writes a slice of bytes as they are
+    ///
+    /// Unlike [`Self::extend_from_slice()`], nothing is written if the
+    /// whole slice doesn't fit
+    ///
+    pub fn put_slice(&mut self, bytes: &[u8]) -> Result<(),CapacityError> {
+        if bytes.len() > self.capacity() - self.len() { return Err(CapacityError); }
+        self.extend_from_slice(bytes).map_err(|_| CapacityError)
+    }
+
+    /// Writes a `u8`
+    pub fn put_u8(&mut self, x: u8) -> Result<(),CapacityError> {
+        self.push(x).map_err(|_| CapacityError)
+    }
+
+    /// Writes an `i8`
+    pub fn put_i8(&mut self, x: i8) -> Result<(),CapacityError> {
+        self.put_u8(x as u8)
+    }
+
+    put_methods! {
+        u16 => put_u16_le, put_u16_be;
+        u32 => put_u32_le, put_u32_be;
+        u64 => put_u64_le, put_u64_be;
+        u128 => put_u128_le, put_u128_be;
+        i16 => put_i16_le, put_i16_be;
+        i32 => put_i32_le, put_i32_be;
+        i64 => put_i64_le, put_i64_be;
+        i128 => put_i128_le, put_i128_be;
+        f32 => put_f32_le, put_f32_be;
+        f64 => put_f64_le, put_f64_be;
+    }
+
+    ///
+    /// Writes an unsigned LEB128 varint, taking 1 to 10 bytes
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::ByteStack;
+    /// let mut buf: ByteStack<16> = ByteStack::new();
+    /// buf.put_varint(5).unwrap();
+    /// buf.put_varint(624485).unwrap();
+    /// assert_eq!(buf, [0x05, 0xe5, 0x8e, 0x26]);
+    /// ```
+    ///
+    pub fn put_varint(&mut self, mut x: u64) -> Result<(),CapacityError> {
+        let mut bytes = [0; 10];
+        let mut n = 0;
+        loop {
+            let byte = (x & 0x7f) as u8;
+            x >>= 7;
+            if x == 0 {
+                bytes[n] = byte;
+                n += 1;
+                break;
+            }
+            bytes[n] = byte | 0x80;
+            n += 1;
+        }
+        self.put_slice(&bytes[..n])
+    }
+
+    ///
+    /// Writes a signed LEB128 varint, taking 1 to 10 bytes
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::ByteStack;
+    /// let mut buf: ByteStack<16> = ByteStack::new();
+    /// buf.put_varint_signed(-1).unwrap();
+    /// buf.put_varint_signed(-123456).unwrap();
+    /// assert_eq!(buf, [0x7f, 0xc0, 0xbb, 0x78]);
+    /// ```
+    ///
+    pub fn put_varint_signed(&mut self, mut x: i64) -> Result<(),CapacityError> {
+        let mut bytes = [0; 10];
+        let mut n = 0;
+        loop {
+            let byte = (x & 0x7f) as u8;
+            x >>= 7;
+            let done = (x == 0 && byte & 0x40 == 0) || (x == -1 && byte & 0x40 != 0);
+            bytes[n] = if done { byte } else { byte | 0x80 };
+            n += 1;
+            if done { break; }
+        }
+        self.put_slice(&bytes[..n])
+    }
+
+    ///
+    /// Writes a slice of bytes prefixed with its length as a varint
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::ByteStack;
+    /// let mut buf: ByteStack<8> = ByteStack::new();
+    /// buf.put_len_prefixed(b"hi").unwrap();
+    /// assert_eq!(buf, [2, b'h', b'i']);
+    ///
+    /// assert!(buf.put_len_prefixed(b"hello").is_err());
+    /// assert_eq!(buf.len(), 3);
+    /// ```
+    ///
+    pub fn put_len_prefixed(&mut self, bytes: &[u8]) -> Result<(),CapacityError> {
+        let len = self.len();
+        self.put_varint(bytes.len() as u64)?;
+        self.put_slice(bytes).inspect_err(|_| self.truncate(len))
+    }
+
+    ///
+    /// Writes a value with its [`Encode`] implementation
+    ///
+    /// If the value doesn't fit, anything it wrote is removed again
+    ///
+    pub fn put_value<E:?Sized+Encode>(&mut self, value: &E) -> Result<(),CapacityError> {
+        let len = self.len();
+        value.encode(self).inspect_err(|_| self.truncate(len))
+    }
+
+    /// Creates a reader over the bytes in the stack
+    pub fn reader(&self) -> ByteReader<'_> {
+        ByteReader::new(self.as_slice())
+    }
+
+}
+
+///
+/// A cursor that decodes values from a slice of bytes, such as those written
+/// to a [`ByteStack`]
+///
+/// Every `get_*` method either reads the whole value and moves past it, or
+/// returns a [`DecodeError`] and leaves the reader where it was.
+///
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize
+}
+
+macro_rules! get_methods {
+    ($($ty:ty => $le:ident, $be:ident;)*) => {$(
+        #[doc = concat!("Reads a `", stringify!($ty), "` in little-endian")]
+        pub fn $le(&mut self) -> Result<$ty,DecodeError> {
+            self.get_array().map(<$ty>::from_le_bytes)
+        }
+
+        #[doc = concat!("Reads a `", stringify!($ty), "` in big-endian")]
+        pub fn $be(&mut self) -> Result<$ty,DecodeError> {
+            self.get_array().map(<$ty>::from_be_bytes)
+        }
+    )*};
+}
+
+impl<'a> ByteReader<'a> {
+
+    /// Creates a reader at the start of the bytes
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// The quantity of bytes read so far
+    pub fn position(&self) -> usize { self.pos }
+
+    /// The bytes that haven't been read yet
+    pub fn remaining(&self) -> &'a [u8] { &self.bytes[self.pos..] }
+
+    /// `true` when every byte has been read
+    pub fn is_empty(&self) -> bool { self.pos == self.bytes.len() }
+
+    /// Reads the next `n` bytes as they are
+    pub fn get_slice(&mut self, n: usize) -> Result<&'a [u8],DecodeError> {
+        let rest = self.remaining();
+        if n > rest.len() { return Err(DecodeError::UnexpectedEnd); }
+        self.pos += n;
+        Ok(&rest[..n])
+    }
+
+    /// Reads the next `M` bytes into an array
+    pub fn get_array<const M:usize>(&mut self) -> Result<[u8;M],DecodeError> {
+        let mut array = [0; M];
+        array.copy_from_slice(self.get_slice(M)?);
+        Ok(array)
+    }
+
+    /// Reads a `u8`
+    pub fn get_u8(&mut self) -> Result<u8,DecodeError> {
+        self.get_array().map(|[x]| x)
+    }
+
+    /// Reads an `i8`
+    pub fn get_i8(&mut self) -> Result<i8,DecodeError> {
+        self.get_u8().map(|x| x as i8)
+    }
+
+    get_methods! {
+        u16 => get_u16_le, get_u16_be;
+        u32 => get_u32_le, get_u32_be;
+        u64 => get_u64_le, get_u64_be;
+        u128 => get_u128_le, get_u128_be;
+        i16 => get_i16_le, get_i16_be;
+        i32 => get_i32_le, get_i32_be;
+        i64 => get_i64_le, get_i64_be;
+        i128 => get_i128_le, get_i128_be;
+        f32 => get_f32_le, get_f32_be;
+        f64 => get_f64_le, get_f64_be;
+    }
+
+    ///
+    /// Reads an unsigned LEB128 varint
+    ///
+    /// # Errors
+    /// Returns [`DecodeError::InvalidVarint`] if the varint doesn't fit in
+    /// a `u64`
+    ///
+    pub fn get_varint(&mut self) -> Result<u64,DecodeError> {
+        let rest = self.remaining();
+        let mut x = 0u64;
+        for (i, &byte) in rest.iter().enumerate() {
+            if i == 9 && byte > 1 { return Err(DecodeError::InvalidVarint); }
+            x |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                self.pos += i + 1;
+                return Ok(x);
+            }
+        }
+        Err(DecodeError::UnexpectedEnd)
+    }
+
+    ///
+    /// Reads a signed LEB128 varint
+    ///
+    /// # Errors
+    /// Returns [`DecodeError::InvalidVarint`] if the varint doesn't fit in
+    /// an `i64`
+    ///
+    pub fn get_varint_signed(&mut self) -> Result<i64,DecodeError> {
+        let rest = self.remaining();
+        let mut x = 0i64;
+        for (i, &byte) in rest.iter().enumerate() {
+            //the 10th byte only holds the sign bit, so it must be all zeros or all ones
+            if i == 9 && byte != 0x00 && byte != 0x7f { return Err(DecodeError::InvalidVarint); }
+            x |= ((byte & 0x7f) as i64) << (7 * i);
+            if byte & 0x80 == 0 {
+                let shift = 7 * (i + 1);
+                if shift < 64 && byte & 0x40 != 0 {
+                    x |= -1 << shift;
+                }
+                self.pos += i + 1;
+                return Ok(x);
+            }
+        }
+        Err(DecodeError::UnexpectedEnd)
+    }
+
+    /// Reads a slice of bytes prefixed with its length as a varint
+    pub fn get_len_prefixed(&mut self) -> Result<&'a [u8],DecodeError> {
+        let pos = self.pos;
+        let len = self.get_varint()?;
+        let len = usize::try_from(len).map_err(|_| DecodeError::UnexpectedEnd);
+        len.and_then(|len| self.get_slice(len)).inspect_err(|_| self.pos = pos)
+    }
+
+    ///
+    /// Reads a value with its [`Decode`] implementation
+    ///
+    /// If the value can't be decoded, the reader is moved back to where it was
+    ///
+    pub fn get_value<D:Decode>(&mut self) -> Result<D,DecodeError> {
+        let pos = self.pos;
+        D::decode(self).inspect_err(|_| self.pos = pos)
+    }
+
+}
+
+macro_rules! impl_primitives {
+    ($($ty:ty => $put:ident, $get:ident;)*) => {$(
+        impl Encode for $ty {
+            fn encode<S:?Sized+Storage<u8>, L:LenType>(&self, buf: &mut StackBase<u8,S,L>) -> Result<(),CapacityError> {
+                buf.$put(*self)
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode(reader: &mut ByteReader<'_>) -> Result<Self,DecodeError> {
+                reader.$get()
+            }
+        }
+    )*};
+}
+
+impl_primitives! {
+    u8 => put_u8, get_u8;
+    u16 => put_u16_le, get_u16_le;
+    u32 => put_u32_le, get_u32_le;
+    u64 => put_u64_le, get_u64_le;
+    u128 => put_u128_le, get_u128_le;
+    i8 => put_i8, get_i8;
+    i16 => put_i16_le, get_i16_le;
+    i32 => put_i32_le, get_i32_le;
+    i64 => put_i64_le, get_i64_le;
+    i128 => put_i128_le, get_i128_le;
+    f32 => put_f32_le, get_f32_le;
+    f64 => put_f64_le, get_f64_le;
+}
+
+impl Encode for bool {
+    fn encode<S:?Sized+Storage<u8>, L:LenType>(&self, buf: &mut StackBase<u8,S,L>) -> Result<(),CapacityError> {
+        buf.put_u8(*self as u8)
+    }
+}
+
+impl Decode for bool {
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self,DecodeError> {
+        match reader.get_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::Invalid)
+        }
+    }
+}
+
+impl<T:Encode, S2:?Sized+Storage<T>, L2:LenType> Encode for StackBase<T,S2,L2> {
+    fn encode<S:?Sized+Storage<u8>, L:LenType>(&self, buf: &mut StackBase<u8,S,L>) -> Result<(),CapacityError> {
+        buf.put_varint(self.len() as u64)?;
+        self.iter().try_for_each(|x| x.encode(buf))
+    }
+}
+
+///
+/// Decodes a stack written by its [`Encode`] implementation
+///
+/// Returns [`DecodeError::Overflow`] if there are more than `N` items
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{ByteStack, Stack, stack, DecodeError};
+/// let mut buf: ByteStack<16> = ByteStack::new();
+/// buf.put_value(&stack![6u16, 2, 8; 4]).unwrap();
+/// assert_eq!(buf, [3, 6, 0, 2, 0, 8, 0]);
+///
+/// assert_eq!(buf.reader().get_value(), Ok(stack![6u16, 2, 8; 4]));
+/// assert_eq!(buf.reader().get_value::<Stack<u16, 2>>(), Err(DecodeError::Overflow));
+/// ```
+///
+impl<T:Decode, const N:usize, L:LenType> Decode for Stack<T,N,L> {
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self,DecodeError> {
+        let len = reader.get_varint()?;
+        if len > N as u64 { return Err(DecodeError::Overflow); }
+        let mut stack = Self::new();
+        for _ in 0..len {
+            stack.push(T::decode(reader)?).ok();
+        }
+        Ok(stack)
+    }
+}
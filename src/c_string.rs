@@ -0,0 +1,284 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::ffi::{c_char, CStr};
+use core::fmt::{self, Debug, Display, Formatter, Result as FmtResult};
+use core::hash::{Hash, Hasher};
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+
+use crate::Stack;
+
+///
+/// Error returned when bytes can't be added to a [`StackCString`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CStringError {
+    /// The bytes contained a NUL before their end
+    InteriorNul,
+    /// The bytes had no NUL to end them
+    MissingNul,
+    /// There wasn't enough capacity left for the bytes and the terminator
+    Overflow
+}
+
+impl Display for CStringError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::InteriorNul => f.write_str("interior nul byte"),
+            Self::MissingNul => f.write_str("missing nul terminator"),
+            Self::Overflow => f.write_str("not enough capacity for the string and its terminator"),
+        }
+    }
+}
+
+///
+/// A NUL-terminated string in a fixed buffer of `N` bytes, for passing to C
+///
+/// The terminator is always kept in the buffer, so at most `N - 1` bytes can
+/// be added, and NUL bytes are rejected. Every method that adds bytes either
+/// adds all of them or returns a [`CStringError`] and leaves the string
+/// unchanged. It dereferences to a [`CStr`].
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{StackCString, CStringError};
+/// use core::fmt::Write;
+///
+/// let mut path: StackCString<16> = StackCString::new();
+/// write!(path, "/dev/ttyS{}", 3).unwrap();
+/// assert_eq!(path.as_c_str(), c"/dev/ttyS3");
+/// assert_eq!(path.as_bytes_with_nul(), b"/dev/ttyS3\0");
+///
+/// assert_eq!(path.push_bytes(b"\0"), Err(CStringError::InteriorNul));
+/// assert_eq!(path.push_str("/too-long"), Err(CStringError::Overflow));
+/// assert_eq!(path.len(), 10);
+/// ```
+///
+#[derive(Clone)]
+pub struct StackCString<const N:usize> {
+    //always holds the bytes followed by a single NUL
+    buf: Stack<u8,N>
+}
+
+impl<const N:usize> StackCString<N> {
+
+    const HAS_ROOM: () = assert!(N > 0, "A StackCString needs room for its terminator");
+
+    /// Creates an empty string
+    pub const fn new() -> Self {
+        let () = Self::HAS_ROOM;
+        let mut buf = [MaybeUninit::uninit(); N];
+        buf[0] = MaybeUninit::new(0);
+        Self { buf: unsafe { Stack::from_raw_parts(buf, 1) } }
+    }
+
+    ///
+    /// Creates a string from the bytes before the first NUL
+    ///
+    /// Anything after the first NUL is ignored.
+    ///
+    /// # Errors
+    /// Returns [`CStringError::MissingNul`] if there is no NUL, or
+    /// [`CStringError::Overflow`] if the string doesn't fit.
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{StackCString, CStringError};
+    /// let name = StackCString::<8>::from_bytes_until_nul(b"eth0\0\0\0\0").unwrap();
+    /// assert_eq!(name.as_bytes(), b"eth0");
+    ///
+    /// assert_eq!(StackCString::<8>::from_bytes_until_nul(b"eth0"), Err(CStringError::MissingNul));
+    /// assert_eq!(StackCString::<4>::from_bytes_until_nul(b"eth0\0"), Err(CStringError::Overflow));
+    /// ```
+    ///
+    pub fn from_bytes_until_nul(bytes: &[u8]) -> Result<Self,CStringError> {
+        let s = CStr::from_bytes_until_nul(bytes).map_err(|_| CStringError::MissingNul)?;
+        let mut string = Self::new();
+        string.push_bytes(s.to_bytes())?;
+        Ok(string)
+    }
+
+    /// The quantity of bytes in the string, not counting the terminator
+    pub fn len(&self) -> usize { self.buf.len() - 1 }
+
+    /// `true` when the string contains no bytes
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// The most bytes the string can hold, not counting the terminator
+    pub fn capacity(&self) -> usize { N - 1 }
+
+    /// Returns the string as a [`CStr`]
+    pub fn as_c_str(&self) -> &CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(self.buf.as_slice()) }
+    }
+
+    /// Returns the bytes of the string without the terminator
+    pub fn as_bytes(&self) -> &[u8] { &self.buf[..self.len()] }
+
+    /// Returns the bytes of the string including the terminator
+    pub fn as_bytes_with_nul(&self) -> &[u8] { &self.buf }
+
+    /// Returns a pointer to the NUL-terminated string for passing to C
+    pub fn as_ptr(&self) -> *const c_char { self.buf.as_ptr() as *const c_char }
+
+    ///
+    /// Returns a pointer to the whole buffer of `N` bytes, for C functions
+    /// that write a string into it
+    ///
+    /// The string is only updated after calling [`Self::set_len_until_nul()`].
+    ///
+    pub fn as_mut_ptr(&mut self) -> *mut c_char { self.buf.as_mut_ptr() as *mut c_char }
+
+    ///
+    /// Sets the length of the string to the position of the first NUL in
+    /// the buffer, after it has been written through [`Self::as_mut_ptr()`]
+    ///
+    /// If there is no NUL, the last byte of the buffer is replaced with one.
+    ///
+    /// # Safety
+    /// Caller must guarrantee that every byte up to and including the first
+    /// NUL is initialized, or every byte of the buffer if there is no NUL
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::StackCString;
+    /// use core::ffi::c_char;
+    ///
+    /// //stands in for a C function like `gethostname()`
+    /// unsafe fn get_name(buf: *mut c_char, len: usize) {
+    ///     core::ptr::copy_nonoverlapping(c"ferris".as_ptr(), buf, 7.min(len));
+    /// }
+    ///
+    /// let mut name: StackCString<32> = StackCString::new();
+    /// unsafe {
+    ///     get_name(name.as_mut_ptr(), 32);
+    ///     name.set_len_until_nul();
+    /// }
+    /// assert_eq!(name.as_c_str(), c"ferris");
+    /// ```
+    ///
+    pub unsafe fn set_len_until_nul(&mut self) {
+        let ptr = self.buf.as_mut_ptr();
+        let mut len = 0;
+        while len < N - 1 && *ptr.add(len) != 0 {
+            len += 1;
+        }
+        *ptr.add(len) = 0;
+        self.buf.set_len(len + 1);
+    }
+
+    ///
+    /// Adds a byte to the end of the string
+    ///
+    /// # Errors
+    /// Returns [`CStringError::InteriorNul`] if the byte is NUL, or
+    /// [`CStringError::Overflow`] if the string is full
+    ///
+    pub fn push(&mut self, byte: u8) -> Result<(),CStringError> {
+        self.push_bytes(&[byte])
+    }
+
+    ///
+    /// Adds bytes to the end of the string
+    ///
+    /// # Errors
+    /// Returns [`CStringError::InteriorNul`] if any of the bytes are NUL, or
+    /// [`CStringError::Overflow`] if they don't all fit
+    ///
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<(),CStringError> {
+        if bytes.contains(&0) { return Err(CStringError::InteriorNul); }
+        if bytes.len() > N - self.buf.len() { return Err(CStringError::Overflow); }
+        self.buf.pop();
+        self.buf.extend_from_slice(bytes).ok();
+        self.buf.push(0).ok();
+        Ok(())
+    }
+
+    /// Adds a string to the end of the string. See [`Self::push_bytes()`]
+    pub fn push_str(&mut self, s: &str) -> Result<(),CStringError> {
+        self.push_bytes(s.as_bytes())
+    }
+
+    /// Shortens the string to `len` bytes, doing nothing if it is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.buf.truncate(len);
+            self.buf.push(0).ok();
+        }
+    }
+
+    /// Removes every byte from the string
+    pub fn clear(&mut self) { self.truncate(0) }
+
+}
+
+impl<const N:usize> Deref for StackCString<N> {
+    type Target = CStr;
+    fn deref(&self) -> &CStr { self.as_c_str() }
+}
+
+impl<const N:usize> AsRef<CStr> for StackCString<N> {
+    fn as_ref(&self) -> &CStr { self.as_c_str() }
+}
+
+impl<const N:usize> Borrow<CStr> for StackCString<N> {
+    fn borrow(&self) -> &CStr { self.as_c_str() }
+}
+
+impl<const N:usize> Default for StackCString<N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<const N:usize> TryFrom<&CStr> for StackCString<N> {
+    type Error = CStringError;
+    fn try_from(s: &CStr) -> Result<Self,CStringError> {
+        Self::from_bytes_until_nul(s.to_bytes_with_nul())
+    }
+}
+
+impl<const N:usize> TryFrom<&str> for StackCString<N> {
+    type Error = CStringError;
+    fn try_from(s: &str) -> Result<Self,CStringError> {
+        let mut string = Self::new();
+        string.push_str(s)?;
+        Ok(string)
+    }
+}
+
+impl<const N:usize> fmt::Write for StackCString<N> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N:usize> Debug for StackCString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+impl<const N:usize> Eq for StackCString<N> {}
+
+impl<const N:usize, const M:usize> PartialEq<StackCString<M>> for StackCString<N> {
+    fn eq(&self, other: &StackCString<M>) -> bool { self.as_bytes() == other.as_bytes() }
+}
+
+impl<const N:usize> PartialEq<CStr> for StackCString<N> {
+    fn eq(&self, other: &CStr) -> bool { self.as_c_str() == other }
+}
+
+impl<const N:usize> PartialEq<&CStr> for StackCString<N> {
+    fn eq(&self, other: &&CStr) -> bool { self.as_c_str() == *other }
+}
+
+impl<const N:usize> PartialOrd for StackCString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<const N:usize> Ord for StackCString<N> {
+    fn cmp(&self, other: &Self) -> Ordering { self.as_c_str().cmp(other.as_c_str()) }
+}
+
+impl<const N:usize> Hash for StackCString<N> {
+    fn hash<H:Hasher>(&self, state: &mut H) { self.as_c_str().hash(state) }
+}
@@ -0,0 +1,672 @@
+//!
+//! An async, bounded, single-producer single-consumer channel
+//!
+//! The values are buffered in an inline [`Queue`] and waiting tasks are
+//! woken through [`Waker`] registration, so there is no dynamic allocation
+//! whatsoever. With the `futures` feature enabled, [`Receiver`] implements
+//! `Stream` and [`Sender`] implements `Sink`.
+//!
+//! Once either half is dropped, the channel is closed: sends fail and
+//! [`Receiver::recv()`] returns `None` after the buffer has been drained.
+//!
+//! # Examples
+//! ```
+//! use stack_stack::channel::channel;
+//! use core::future::Future;
+//! use core::pin::pin;
+//! use core::task::{Context, Poll, Waker};
+//!
+//! let mut ch = channel::<i32, 2>();
+//! let (mut tx, mut rx) = ch.split();
+//!
+//! let mut producer = pin!(async move {
+//!     for i in 0..10 {
+//!         tx.send(i).await.unwrap();
+//!     }
+//! });
+//!
+//! let mut consumer = pin!(async move {
+//!     let mut received = Vec::new();
+//!     while let Some(x) = rx.recv().await {
+//!         received.push(x);
+//!     }
+//!     received
+//! });
+//!
+//! //a tiny round-robin executor
+//! let mut cx = Context::from_waker(Waker::noop());
+//! let mut sent = false;
+//! let received = loop {
+//!     if !sent {
+//!         sent = producer.as_mut().poll(&mut cx).is_ready();
+//!     }
+//!     if let Poll::Ready(received) = consumer.as_mut().poll(&mut cx) {
+//!         break received;
+//!     }
+//! };
+//!
+//! assert_eq!(received, (0..10).collect::<Vec<_>>());
+//! ```
+//!
+//! Here each task is only polled again after it has been woken, so a lost
+//! wake-up in either direction makes the executor stall instead of passing
+//! ```
+//! use stack_stack::channel::channel;
+//! use std::future::Future;
+//! use std::pin::pin;
+//! use std::sync::Arc;
+//! use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+//! use std::task::{Context, Poll, Wake, Waker};
+//!
+//! struct CountingWaker(AtomicUsize);
+//! impl Wake for CountingWaker {
+//!     fn wake(self: Arc<Self>) { self.0.fetch_add(1, SeqCst); }
+//! }
+//!
+//! let mut ch = channel::<u32, 2>();
+//! let (mut tx, mut rx) = ch.split();
+//!
+//! let mut producer = pin!(async move {
+//!     for i in 0..100 {
+//!         tx.send(i).await.unwrap();
+//!     }
+//! });
+//!
+//! let mut consumer = pin!(async move {
+//!     let mut received = Vec::new();
+//!     while let Some(x) = rx.recv().await {
+//!         received.push(x);
+//!     }
+//!     received
+//! });
+//!
+//! //both tasks start out woken
+//! let producer_wakes = Arc::new(CountingWaker(AtomicUsize::new(1)));
+//! let consumer_wakes = Arc::new(CountingWaker(AtomicUsize::new(1)));
+//! let producer_waker = Waker::from(producer_wakes.clone());
+//! let consumer_waker = Waker::from(consumer_wakes.clone());
+//!
+//! let mut sent = false;
+//! let (mut producer_polls, mut consumer_polls) = (0, 0);
+//! let received = loop {
+//!     let mut progressed = false;
+//!     if !sent && producer_wakes.0.swap(0, SeqCst) > 0 {
+//!         progressed = true;
+//!         producer_polls += 1;
+//!         sent = producer.as_mut().poll(&mut Context::from_waker(&producer_waker)).is_ready();
+//!     }
+//!     if consumer_wakes.0.swap(0, SeqCst) > 0 {
+//!         progressed = true;
+//!         consumer_polls += 1;
+//!         if let Poll::Ready(received) = consumer.as_mut().poll(&mut Context::from_waker(&consumer_waker)) {
+//!             break received;
+//!         }
+//!     }
+//!     assert!(progressed, "both tasks are waiting, but neither was woken");
+//! };
+//!
+//! assert_eq!(received, (0..100).collect::<Vec<_>>());
+//!
+//! //the producer waited on a full buffer and the consumer on an empty one
+//! assert!(producer_polls > 1);
+//! assert!(consumer_polls > 1);
+//! ```
+//!
+
+use core::cell::UnsafeCell;
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use crate::spsc::{Consumer, Producer, Queue};
+
+///
+/// Creates an empty channel
+///
+/// Equivalent to [`Channel::new()`]
+///
+pub const fn channel<T, const N:usize>() -> Channel<T,N> {
+    Channel::new()
+}
+
+///
+/// The shared state of a bounded async channel
+///
+/// Since [`Self::new()`] is `const`, a channel can be placed in a `static`
+/// and shared between tasks using [`Self::split_unchecked()`].
+///
+pub struct Channel<T, const N:usize> {
+    queue: Queue<T,N>,
+    closed: AtomicBool,
+    send_waker: AtomicWaker,
+    recv_waker: AtomicWaker
+}
+
+impl<T, const N:usize> Default for Channel<T,N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T, const N:usize> Debug for Channel<T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Channel")
+            .field("len", &self.queue.len())
+            .field("capacity", &N)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<T, const N:usize> Channel<T,N> {
+
+    /// Creates an empty channel
+    pub const fn new() -> Self {
+        Self {
+            queue: Queue::new(),
+            closed: AtomicBool::new(false),
+            send_waker: AtomicWaker::new(),
+            recv_waker: AtomicWaker::new()
+        }
+    }
+
+    /// The quantity of values currently buffered in the channel
+    pub fn len(&self) -> usize { self.queue.len() }
+
+    /// The total quantity of values that this channel can buffer.
+    /// Equivalent to `N`
+    pub const fn capacity(&self) -> usize { N }
+
+    /// `true` when no values are buffered in the channel
+    pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    /// `true` when either half of the channel has been dropped
+    pub fn is_closed(&self) -> bool { self.closed.load(Ordering::Acquire) }
+
+    ///
+    /// Splits the channel into its sending and receiving halves
+    ///
+    /// This also reopens the channel if it was previously closed
+    ///
+    pub fn split(&mut self) -> (Sender<'_,T,N>, Receiver<'_,T,N>) {
+        *self.closed.get_mut() = false;
+        let (producer, consumer) = self.queue.split();
+        (
+            Sender { producer, closed: &self.closed, send_waker: &self.send_waker, recv_waker: &self.recv_waker },
+            Receiver { consumer, closed: &self.closed, send_waker: &self.send_waker, recv_waker: &self.recv_waker }
+        )
+    }
+
+    ///
+    /// Same as [`Self::split()`] but only requires a shared reference, so
+    /// that it can be used on a channel stored in a `static`
+    ///
+    /// Unlike [`Self::split()`], this does *not* reopen a closed channel.
+    ///
+    /// # Safety
+    /// Caller must guarrantee that at no point are there more than one
+    /// [`Sender`] and one [`Receiver`] alive for this channel
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::channel::Channel;
+    /// static CHANNEL: Channel<u32, 8> = Channel::new();
+    ///
+    /// let (mut tx, mut rx) = unsafe { CHANNEL.split_unchecked() };
+    /// assert_eq!(tx.try_send(6), Ok(()));
+    /// assert_eq!(rx.try_recv(), Some(6));
+    /// ```
+    ///
+    pub unsafe fn split_unchecked(&self) -> (Sender<'_,T,N>, Receiver<'_,T,N>) {
+        let (producer, consumer) = self.queue.split_unchecked();
+        (
+            Sender { producer, closed: &self.closed, send_waker: &self.send_waker, recv_waker: &self.recv_waker },
+            Receiver { consumer, closed: &self.closed, send_waker: &self.send_waker, recv_waker: &self.recv_waker }
+        )
+    }
+
+}
+
+///
+/// The sending half of a [`Channel`]
+///
+/// Dropping it closes the channel and wakes the receiver
+///
+pub struct Sender<'a, T, const N:usize> {
+    producer: Producer<'a,T,N>,
+    closed: &'a AtomicBool,
+    send_waker: &'a AtomicWaker,
+    recv_waker: &'a AtomicWaker
+}
+
+impl<T, const N:usize> Drop for Sender<'_,T,N> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        self.recv_waker.wake();
+    }
+}
+
+impl<T, const N:usize> Debug for Sender<'_,T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Sender")
+            .field("len", &self.producer.len())
+            .field("capacity", &N)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<'a, T, const N:usize> Sender<'a,T,N> {
+
+    /// `true` when the [`Receiver`] has been dropped
+    pub fn is_closed(&self) -> bool { self.closed.load(Ordering::Acquire) }
+
+    ///
+    /// Attempts to send a value without waiting
+    ///
+    /// If the channel is full or closed, the value is returned in an `Err()`
+    ///
+    pub fn try_send(&mut self, x:T) -> Result<(),T> {
+        if self.is_closed() { return Err(x); }
+        self.producer.push(x)?;
+        self.recv_waker.wake();
+        Ok(())
+    }
+
+    ///
+    /// Sends a value, waiting while the channel is full
+    ///
+    /// If the [`Receiver`] has been dropped, the value is returned in an `Err()`
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::channel::channel;
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    /// use std::task::{Context, Poll, Wake, Waker};
+    ///
+    /// struct CountingWaker(AtomicUsize);
+    /// impl Wake for CountingWaker {
+    ///     fn wake(self: Arc<Self>) { self.0.fetch_add(1, SeqCst); }
+    /// }
+    ///
+    /// let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    /// let waker = Waker::from(wakes.clone());
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// let mut ch = channel::<i32, 1>();
+    /// let (mut tx, mut rx) = ch.split();
+    /// assert_eq!(tx.try_send(1), Ok(()));
+    ///
+    /// //the buffer is full, so the send waits
+    /// let mut send = tx.send(2);
+    /// assert_eq!(Pin::new(&mut send).poll(&mut cx), Poll::Pending);
+    /// assert_eq!(wakes.0.load(SeqCst), 0);
+    ///
+    /// //receiving makes room and wakes the sender
+    /// assert_eq!(rx.try_recv(), Some(1));
+    /// assert_eq!(wakes.0.load(SeqCst), 1);
+    /// assert_eq!(Pin::new(&mut send).poll(&mut cx), Poll::Ready(Ok(())));
+    /// assert_eq!(rx.try_recv(), Some(2));
+    ///
+    /// //dropping the receiver wakes a waiting sender and gives the value back
+    /// assert_eq!(tx.try_send(3), Ok(()));
+    /// let mut send = tx.send(4);
+    /// assert_eq!(Pin::new(&mut send).poll(&mut cx), Poll::Pending);
+    /// drop(rx);
+    /// assert_eq!(wakes.0.load(SeqCst), 2);
+    /// assert_eq!(Pin::new(&mut send).poll(&mut cx), Poll::Ready(Err(4)));
+    /// ```
+    ///
+    pub fn send(&mut self, x:T) -> SendFuture<'_,'a,T,N> {
+        SendFuture { sender: self, value: Some(x) }
+    }
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(),()>> {
+        if self.is_closed() { return Poll::Ready(Err(())); }
+        if !self.producer.is_full() { return Poll::Ready(Ok(())); }
+
+        //check again after registering so that a concurrent pop isn't missed
+        self.send_waker.register(cx.waker());
+        if self.is_closed() {
+            Poll::Ready(Err(()))
+        } else if !self.producer.is_full() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+}
+
+///
+/// Future returned by [`Sender::send()`]
+///
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SendFuture<'s, 'a, T, const N:usize> {
+    sender: &'s mut Sender<'a,T,N>,
+    value: Option<T>
+}
+
+impl<T, const N:usize> Unpin for SendFuture<'_,'_,T,N> {}
+
+impl<T, const N:usize> Debug for SendFuture<'_,'_,T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SendFuture").field("sender", self.sender).finish_non_exhaustive()
+    }
+}
+
+impl<T, const N:usize> Future for SendFuture<'_,'_,T,N> {
+    type Output = Result<(),T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let x = this.value.take().expect("`SendFuture` polled after completion");
+        match this.sender.poll_ready(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(this.sender.try_send(x)),
+            Poll::Ready(Err(())) => Poll::Ready(Err(x)),
+            Poll::Pending => {
+                this.value = Some(x);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+///
+/// The receiving half of a [`Channel`]
+///
+/// Dropping it closes the channel and wakes the sender
+///
+pub struct Receiver<'a, T, const N:usize> {
+    consumer: Consumer<'a,T,N>,
+    closed: &'a AtomicBool,
+    send_waker: &'a AtomicWaker,
+    recv_waker: &'a AtomicWaker
+}
+
+impl<T, const N:usize> Drop for Receiver<'_,T,N> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        self.send_waker.wake();
+    }
+}
+
+impl<T, const N:usize> Debug for Receiver<'_,T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Receiver")
+            .field("len", &self.consumer.len())
+            .field("capacity", &N)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<'a, T, const N:usize> Receiver<'a,T,N> {
+
+    /// `true` when the [`Sender`] has been dropped
+    pub fn is_closed(&self) -> bool { self.closed.load(Ordering::Acquire) }
+
+    ///
+    /// Attempts to receive a value without waiting
+    ///
+    /// Returns `None` if the channel is currently empty
+    ///
+    pub fn try_recv(&mut self) -> Option<T> {
+        let x = self.consumer.pop()?;
+        self.send_waker.wake();
+        Some(x)
+    }
+
+    ///
+    /// Receives a value, waiting while the channel is empty
+    ///
+    /// Returns `None` once the [`Sender`] has been dropped and all buffered
+    /// values have been received
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::channel::channel;
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    /// use std::task::{Context, Poll, Wake, Waker};
+    ///
+    /// struct CountingWaker(AtomicUsize);
+    /// impl Wake for CountingWaker {
+    ///     fn wake(self: Arc<Self>) { self.0.fetch_add(1, SeqCst); }
+    /// }
+    ///
+    /// let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    /// let waker = Waker::from(wakes.clone());
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// let mut ch = channel::<i32, 1>();
+    /// let (mut tx, mut rx) = ch.split();
+    ///
+    /// //the buffer is empty, so the receive waits
+    /// assert_eq!(Pin::new(&mut rx.recv()).poll(&mut cx), Poll::Pending);
+    /// assert_eq!(wakes.0.load(SeqCst), 0);
+    ///
+    /// //sending wakes the receiver
+    /// assert_eq!(tx.try_send(1), Ok(()));
+    /// assert_eq!(wakes.0.load(SeqCst), 1);
+    /// assert_eq!(Pin::new(&mut rx.recv()).poll(&mut cx), Poll::Ready(Some(1)));
+    ///
+    /// //dropping the sender wakes the receiver, which then sees the end
+    /// assert_eq!(Pin::new(&mut rx.recv()).poll(&mut cx), Poll::Pending);
+    /// drop(tx);
+    /// assert_eq!(wakes.0.load(SeqCst), 2);
+    /// assert_eq!(Pin::new(&mut rx.recv()).poll(&mut cx), Poll::Ready(None));
+    /// ```
+    ///
+    pub fn recv(&mut self) -> RecvFuture<'_,'a,T,N> {
+        RecvFuture { receiver: self }
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if let Some(x) = self.try_recv() { return Poll::Ready(Some(x)); }
+        if self.is_closed() { return Poll::Ready(self.try_recv()); }
+
+        //check again after registering so that a concurrent push isn't missed
+        self.recv_waker.register(cx.waker());
+        match self.try_recv() {
+            Some(x) => Poll::Ready(Some(x)),
+            None if self.is_closed() => Poll::Ready(self.try_recv()),
+            None => Poll::Pending
+        }
+    }
+
+}
+
+///
+/// Future returned by [`Receiver::recv()`]
+///
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvFuture<'r, 'a, T, const N:usize> {
+    receiver: &'r mut Receiver<'a,T,N>
+}
+
+impl<T, const N:usize> Debug for RecvFuture<'_,'_,T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("RecvFuture").field("receiver", self.receiver).finish()
+    }
+}
+
+impl<T, const N:usize> Future for RecvFuture<'_,'_,T,N> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+///
+/// Error returned by the `Sink` implementation of [`Sender`] when the
+/// [`Receiver`] has been dropped
+///
+/// # Examples
+/// ```
+/// # use stack_stack::channel::{channel, Closed};
+/// use futures_core::Stream;
+/// use futures_sink::Sink;
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll, Waker};
+///
+/// let mut cx = Context::from_waker(Waker::noop());
+/// let mut ch = channel::<i32, 2>();
+/// let (mut tx, mut rx) = ch.split();
+///
+/// for i in 0..2 {
+///     assert_eq!(Pin::new(&mut tx).poll_ready(&mut cx), Poll::Ready(Ok(())));
+///     assert_eq!(Pin::new(&mut tx).start_send(i), Ok(()));
+/// }
+///
+/// //the buffer is full until the stream is read from
+/// assert_eq!(Pin::new(&mut tx).poll_ready(&mut cx), Poll::Pending);
+/// assert_eq!(rx.size_hint(), (2, None));
+/// assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(Some(0)));
+/// assert_eq!(Pin::new(&mut tx).poll_ready(&mut cx), Poll::Ready(Ok(())));
+/// assert_eq!(Pin::new(&mut tx).start_send(2), Ok(()));
+///
+/// //closing the sink ends the stream once it has been drained
+/// assert_eq!(Pin::new(&mut tx).poll_close(&mut cx), Poll::Ready(Ok(())));
+/// assert_eq!(rx.size_hint(), (2, Some(2)));
+/// assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(Some(1)));
+/// assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(Some(2)));
+/// assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(None));
+///
+/// //once the receiver is gone, the sink reports `Closed`
+/// drop(rx);
+/// assert_eq!(Pin::new(&mut tx).poll_ready(&mut cx), Poll::Ready(Err(Closed)));
+/// assert_eq!(Pin::new(&mut tx).start_send(3), Err(Closed));
+/// ```
+///
+/// # Panics
+/// `start_send` panics if the buffer is full because `poll_ready` was skipped,
+/// the same as [`Extend`] on a full stack
+///
+/// ```should_panic
+/// # use stack_stack::channel::channel;
+/// use futures_sink::Sink;
+/// use std::pin::Pin;
+///
+/// let mut ch = channel::<i32, 1>();
+/// let (mut tx, _rx) = ch.split();
+///
+/// Pin::new(&mut tx).start_send(1).ok();
+/// Pin::new(&mut tx).start_send(2).ok();
+/// ```
+///
+#[cfg(feature = "futures")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Closed;
+
+#[cfg(feature = "futures")]
+impl<T, const N:usize> futures_core::Stream for Receiver<'_,T,N> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.poll_recv(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.consumer.len(), if self.is_closed() { Some(self.consumer.len()) } else { None })
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T, const N:usize> futures_sink::Sink<T> for Sender<'_,T,N> {
+    type Error = Closed;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(),Closed>> {
+        Sender::poll_ready(&mut self, cx).map_err(|()| Closed)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(),Closed> {
+        if self.is_closed() { return Err(Closed); }
+
+        //`poll_ready` returned `Ok`, so only this sender could have filled the
+        //buffer since. Skipping it breaks the `Sink` contract, and panicking
+        //is the only way to report that without losing the value silently
+        if self.producer.push(item).is_err() {
+            panic!("Attempted to send without waiting for `poll_ready`, but the buffer was full");
+        }
+        self.recv_waker.wake();
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),Closed>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),Closed>> {
+        self.closed.store(true, Ordering::Release);
+        self.recv_waker.wake();
+        Poll::Ready(Ok(()))
+    }
+}
+
+const WAITING: usize = 0;
+const REGISTERING: usize = 0b01;
+const WAKING: usize = 0b10;
+
+///
+/// A slot for a single [`Waker`] that can be registered and woken from
+/// different threads without locking
+///
+/// The state machine mirrors the one used by `futures::task::AtomicWaker`
+///
+struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>
+}
+
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+
+    const fn new() -> Self {
+        Self { state: AtomicUsize::new(WAITING), waker: UnsafeCell::new(None) }
+    }
+
+    fn register(&self, waker: &Waker) {
+        match self.state.compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => unsafe {
+                let slot = &mut *self.waker.get();
+                let old = match slot {
+                    Some(current) if current.will_wake(waker) => None,
+                    _ => slot.replace(waker.clone())
+                };
+
+                let res = self.state.compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire);
+                if res.is_err() {
+                    //a wake-up came in while we were registering
+                    let waker = slot.take();
+                    self.state.swap(WAITING, Ordering::AcqRel);
+                    if let Some(waker) = waker { waker.wake(); }
+                }
+                drop(old);
+            },
+
+            //currently being woken, so just have the task poll again
+            Err(WAKING) => waker.wake_by_ref(),
+
+            //only one task ever registers with each slot
+            Err(_) => {}
+        }
+    }
+
+    fn wake(&self) {
+        if self.state.fetch_or(WAKING, Ordering::AcqRel) == WAITING {
+            let waker = unsafe { (*self.waker.get()).take() };
+            self.state.fetch_and(!WAKING, Ordering::Release);
+            if let Some(waker) = waker { waker.wake(); }
+        }
+    }
+
+}
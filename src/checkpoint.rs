@@ -0,0 +1,112 @@
+use crate::{LenType, StackBase, Storage};
+
+///
+/// A saved length of a [`Stack`](crate::Stack) that it can later be rolled back to
+///
+/// Created with [`StackBase::mark()`] and consumed by [`StackBase::rollback_to()`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    len: usize
+}
+
+/// Rolls a stack back when dropped, including during unwinding
+struct Rollback<'a, T, S:?Sized+Storage<T>, L:LenType> {
+    stack: &'a mut StackBase<T,S,L>,
+    len: Option<usize>
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> Drop for Rollback<'_,T,S,L> {
+    fn drop(&mut self) {
+        if let Some(len) = self.len {
+            self.stack.truncate(len);
+        }
+    }
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> StackBase<T,S,L> {
+
+    ///
+    /// Saves the current length of the stack so that any values pushed
+    /// afterwards can be removed with [`Self::rollback_to()`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2; 5];
+    ///
+    /// let mark = s.mark();
+    /// s.push(8).ok();
+    /// s.push(3).ok();
+    /// assert_eq!(s, [6, 2, 8, 3]);
+    ///
+    /// s.rollback_to(mark);
+    /// assert_eq!(s, [6, 2]);
+    /// ```
+    ///
+    pub fn mark(&self) -> Checkpoint {
+        Checkpoint { len: self.len() }
+    }
+
+    ///
+    /// Drops every value pushed since the [`Checkpoint`] was taken
+    ///
+    /// # Panics
+    /// In debug builds, panics if the stack is now shorter than it was when
+    /// the checkpoint was taken. In release builds, the stack is left
+    /// unchanged instead.
+    ///
+    /// A checkpoint only records a length, so one that the stack was shortened
+    /// past and then grew back over is not detected, and rolling back to it
+    /// truncates whatever was pushed back.
+    ///
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) {
+        debug_assert!(
+            checkpoint.len <= self.len(),
+            "Attempted to roll back to len {}, but the len was {}",
+            checkpoint.len, self.len()
+        );
+        self.truncate(checkpoint.len);
+    }
+
+    ///
+    /// Runs a closure on the stack, undoing all of its pushes if it fails
+    ///
+    /// If the closure returns an `Err()` or panics, the stack is rolled back
+    /// to the length it had beforehand. Any values the closure popped from
+    /// below that length are not restored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2; 5];
+    ///
+    /// let res: Result<(), &str> = s.transaction(|s| {
+    ///     s.push(8).ok();
+    ///     s.push(3).ok();
+    ///     Err("backtrack")
+    /// });
+    /// assert_eq!(res, Err("backtrack"));
+    /// assert_eq!(s, [6, 2]);
+    ///
+    /// let res: Result<usize, &str> = s.transaction(|s| {
+    ///     s.push(8).ok();
+    ///     Ok(s.len())
+    /// });
+    /// assert_eq!(res, Ok(3));
+    /// assert_eq!(s, [6, 2, 8]);
+    /// ```
+    ///
+    pub fn transaction<R, E, F>(&mut self, f:F) -> Result<R,E>
+    where F: FnOnce(&mut Self) -> Result<R,E>
+    {
+        let len = Some(self.len());
+        let mut rollback = Rollback { stack: self, len };
+        let res = f(rollback.stack);
+        if res.is_ok() {
+            rollback.len = None;
+        }
+        res
+    }
+
+}
@@ -0,0 +1,317 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::cmp::Ordering;
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use core::hash::{Hash, Hasher};
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+
+use crate::{IntoIter, LenType, Stack};
+use crate::len::{len_from_usize, len_to_usize};
+
+///
+/// A [`Stack`] of `Copy` values that is itself `Copy`
+///
+/// Since [`Stack`] implements `Drop`, it can't be `Copy` even when its items
+/// are. This twin type has no `Drop`, so it can be stored in `Copy` structs,
+/// `const` tables and `#[repr(C)]` messages.
+///
+/// It has the exact same layout as a [`Stack`] of the same parameters and
+/// dereferences to one, so every method of [`Stack`] taking `&self` or
+/// `&mut self` is available directly, without duplicating the whole API.
+/// Converting between the two with [`From`] is free. Generic code that
+/// should accept either type can take an [`AsStack`].
+///
+/// Like [`Stack`], the length can be stored in a smaller [`LenType`], so
+/// that a `CopyStack<u8, 16, u8>` takes only 17 bytes.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{CopyStack, Stack, stack};
+/// #[derive(Clone, Copy)]
+/// struct Message {
+///     id: u32,
+///     payload: CopyStack<u8, 8>
+/// }
+///
+/// const EMPTY: Message = Message { id: 0, payload: CopyStack::new() };
+///
+/// let mut msg = EMPTY;
+/// msg.payload.extend_from_slice(b"hi").ok();
+/// let copy = msg;
+/// msg.payload.push(b'!').ok();
+///
+/// assert_eq!(copy.payload, *b"hi");
+/// assert_eq!(msg.payload, *b"hi!");
+///
+/// let s: Stack<u8, 8> = msg.payload.into();
+/// assert_eq!(s, stack![b'h', b'i', b'!'; 8]);
+/// ```
+///
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CopyStack<T:Copy, const N:usize, L:LenType = usize> {
+    len: L,
+    data: [MaybeUninit<T>; N]
+}
+
+//used to convert the array in `const` contexts, where `map()` isn't available
+union Uninit<T:Copy, const N:usize> {
+    init: [T;N],
+    uninit: [MaybeUninit<T>;N]
+}
+
+impl<T:Copy, const N:usize, L:LenType> CopyStack<T,N,L> {
+
+    const LEN_FITS: () = assert!(N <= L::MAX, "The capacity of the stack doesn't fit in its length type");
+
+    /// Creates an empty stack
+    pub const fn new() -> Self {
+        let () = Self::LEN_FITS;
+        Self { len: L::ZERO, data: [MaybeUninit::uninit(); N] }
+    }
+
+    ///
+    /// Creates a full stack from the values of an array
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::CopyStack;
+    /// const PRIMES: CopyStack<u32, 5> = CopyStack::from_array([2, 3, 5, 7, 11]);
+    /// assert_eq!(PRIMES, [2, 3, 5, 7, 11]);
+    /// assert!(PRIMES.is_full());
+    /// ```
+    ///
+    pub const fn from_array(array: [T;N]) -> Self {
+        Self::using_array(array, N)
+    }
+
+    ///
+    /// Creates a stack from the values of an array up to the given length
+    ///
+    /// # Panics
+    /// Panics if the length is greater than the size of the array
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::CopyStack;
+    /// const TABLE: CopyStack<u8, 5> = CopyStack::using_array([6, 2, 8, 0, 0], 3);
+    /// assert_eq!(TABLE, [6, 2, 8]);
+    /// assert_eq!(TABLE.capacity(), 5);
+    /// ```
+    ///
+    pub const fn using_array(array: [T;N], len: usize) -> Self {
+        if len > N {
+            panic!("Attempted to create a stack with a len greater than the array");
+        }
+        let () = Self::LEN_FITS;
+        Self { len: len_from_usize(len), data: unsafe { Uninit { init: array }.uninit } }
+    }
+
+    /// The quantity of elements in the stack
+    pub const fn len(&self) -> usize { len_to_usize(&self.len) }
+
+    /// `true` when the stack contains no elements
+    pub const fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Returns a slice of the data in the stack
+    pub const fn as_slice(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr() as *const T, self.len()) }
+    }
+
+    /// Converts this into a regular [`Stack`]
+    pub fn into_stack(self) -> Stack<T,N,L> { self.into() }
+
+    /// Same as [`Stack::resize_capacity()`]
+    pub fn resize_capacity<const M:usize>(self) -> CopyStack<T,M,L> {
+        self.into_stack().resize_capacity().into()
+    }
+
+    /// Same as [`Stack::try_resize_capacity()`]
+    pub fn try_resize_capacity<const M:usize>(self) -> Result<CopyStack<T,M,L>,Self> {
+        match self.into_stack().try_resize_capacity() {
+            Ok(s) => Ok(s.into()),
+            Err(s) => Err(s.into())
+        }
+    }
+
+    /// Same as [`Stack::grow()`]
+    pub fn grow<const M:usize>(self) -> CopyStack<T,M,L> {
+        self.into_stack().grow().into()
+    }
+
+}
+
+///
+/// Borrows a value as a [`Stack`], so that generic code can accept both a
+/// [`Stack`] and a [`CopyStack`]
+///
+/// Use [`Stack::as_view()`] on the result to also erase the capacity.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{AsStack, CopyStack, Stack, stack};
+/// fn push_checksum<S:AsStack<u8, 8>>(s: &mut S) -> Result<(), u8> {
+///     let stack = s.as_stack_mut();
+///     let sum = stack.iter().fold(0u8, |sum, x| sum.wrapping_add(*x));
+///     stack.push(sum)
+/// }
+///
+/// let mut s1: Stack<u8, 8> = stack![6, 2, 8; 8];
+/// let mut s2: CopyStack<u8, 8> = CopyStack::from_array([1; 8]);
+///
+/// assert_eq!(push_checksum(&mut s1), Ok(()));
+/// assert_eq!(push_checksum(&mut s2), Err(8));
+/// assert_eq!(s1, [6, 2, 8, 16]);
+/// ```
+///
+pub trait AsStack<T, const N:usize, L:LenType = usize> {
+    /// Borrows this as a [`Stack`]
+    fn as_stack(&self) -> &Stack<T,N,L>;
+
+    /// Mutably borrows this as a [`Stack`]
+    fn as_stack_mut(&mut self) -> &mut Stack<T,N,L>;
+}
+
+impl<T, const N:usize, L:LenType> AsStack<T,N,L> for Stack<T,N,L> {
+    fn as_stack(&self) -> &Stack<T,N,L> { self }
+    fn as_stack_mut(&mut self) -> &mut Stack<T,N,L> { self }
+}
+
+impl<T:Copy, const N:usize, L:LenType> AsStack<T,N,L> for CopyStack<T,N,L> {
+    fn as_stack(&self) -> &Stack<T,N,L> {
+        //SAFETY: both types are `repr(C)` with the same fields
+        unsafe { &*(self as *const Self as *const Stack<T,N,L>) }
+    }
+
+    fn as_stack_mut(&mut self) -> &mut Stack<T,N,L> {
+        unsafe { &mut *(self as *mut Self as *mut Stack<T,N,L>) }
+    }
+}
+
+impl<T:Copy, const N:usize, L:LenType> Deref for CopyStack<T,N,L> {
+    type Target = Stack<T,N,L>;
+    fn deref(&self) -> &Stack<T,N,L> { self.as_stack() }
+}
+
+impl<T:Copy, const N:usize, L:LenType> DerefMut for CopyStack<T,N,L> {
+    fn deref_mut(&mut self) -> &mut Stack<T,N,L> { self.as_stack_mut() }
+}
+
+impl<T:Copy, const N:usize, L:LenType> From<Stack<T,N,L>> for CopyStack<T,N,L> {
+    fn from(stack: Stack<T,N,L>) -> Self {
+        let stack = ManuallyDrop::new(stack);
+        unsafe { *(&*stack as *const Stack<T,N,L> as *const Self) }
+    }
+}
+
+impl<T:Copy, const N:usize, L:LenType> From<CopyStack<T,N,L>> for Stack<T,N,L> {
+    fn from(stack: CopyStack<T,N,L>) -> Self {
+        unsafe { core::ptr::read(&stack as *const CopyStack<T,N,L> as *const Self) }
+    }
+}
+
+impl<T:Copy, const N:usize> From<[T;N]> for CopyStack<T,N> {
+    fn from(array: [T;N]) -> Self { Self::from_array(array) }
+}
+
+impl<T:Copy, const N:usize, L:LenType> Default for CopyStack<T,N,L> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T:Copy, const N:usize, L:LenType> AsRef<[T]> for CopyStack<T,N,L> {
+    fn as_ref(&self) -> &[T] { self.as_slice() }
+}
+
+impl<T:Copy, const N:usize, L:LenType> AsMut<[T]> for CopyStack<T,N,L> {
+    fn as_mut(&mut self) -> &mut [T] { self.as_mut_slice() }
+}
+
+impl<T:Copy, const N:usize, L:LenType> Borrow<[T]> for CopyStack<T,N,L> {
+    fn borrow(&self) -> &[T] { self.as_slice() }
+}
+
+impl<T:Copy, const N:usize, L:LenType> BorrowMut<[T]> for CopyStack<T,N,L> {
+    fn borrow_mut(&mut self) -> &mut [T] { self.as_mut_slice() }
+}
+
+impl<T:Copy+Eq, const N:usize, L:LenType> Eq for CopyStack<T,N,L> {}
+
+impl<T:Copy+PartialEq<U>, U:Copy, const N:usize, const M:usize, L:LenType, L2:LenType> PartialEq<CopyStack<U,M,L2>> for CopyStack<T,N,L> {
+    fn eq(&self, other: &CopyStack<U,M,L2>) -> bool { self.as_slice().eq(other.as_slice()) }
+}
+
+impl<T:Copy+PartialEq<U>, U, const N:usize, const M:usize, L:LenType, L2:LenType> PartialEq<Stack<U,M,L2>> for CopyStack<T,N,L> {
+    fn eq(&self, other: &Stack<U,M,L2>) -> bool { self.as_slice().eq(other.as_slice()) }
+}
+
+impl<T:Copy+PartialEq<U>, U, const N:usize, const M:usize, L:LenType> PartialEq<[U;M]> for CopyStack<T,N,L> {
+    fn eq(&self, other: &[U;M]) -> bool { self.as_slice().eq(other) }
+}
+
+impl<T:Copy+PartialEq<U>, U, const N:usize, L:LenType> PartialEq<[U]> for CopyStack<T,N,L> {
+    fn eq(&self, other: &[U]) -> bool { self.as_slice().eq(other) }
+}
+
+impl<T:Copy+PartialEq<U>, U, const N:usize, L:LenType> PartialEq<&[U]> for CopyStack<T,N,L> {
+    fn eq(&self, other: &&[U]) -> bool { self.as_slice().eq(*other) }
+}
+
+impl<T:Copy+PartialOrd, const N:usize, const M:usize, L:LenType, L2:LenType> PartialOrd<CopyStack<T,M,L2>> for CopyStack<T,N,L> {
+    fn partial_cmp(&self, other: &CopyStack<T,M,L2>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T:Copy+Ord, const N:usize, L:LenType> Ord for CopyStack<T,N,L> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T:Copy+Hash, const N:usize, L:LenType> Hash for CopyStack<T,N,L> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<T:Copy+Debug, const N:usize, L:LenType> Debug for CopyStack<T,N,L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T:Copy, const N:usize, L:LenType> Extend<T> for CopyStack<T,N,L> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        (**self).extend(iter)
+    }
+}
+
+impl<'a, T:Copy+'a, const N:usize, L:LenType> Extend<&'a T> for CopyStack<T,N,L> {
+    fn extend<I:IntoIterator<Item=&'a T>>(&mut self, iter:I) {
+        (**self).extend(iter)
+    }
+}
+
+impl<T:Copy, const N:usize, L:LenType> IntoIterator for CopyStack<T,N,L> {
+    type Item = T;
+    type IntoIter = IntoIter<T,N,L>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_stack().into_iter()
+    }
+}
+
+impl<'a, T:Copy, const N:usize, L:LenType> IntoIterator for &'a CopyStack<T,N,L> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a,T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T:Copy, const N:usize, L:LenType> IntoIterator for &'a mut CopyStack<T,N,L> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a,T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
@@ -0,0 +1,324 @@
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use core::mem::{replace, MaybeUninit};
+use core::ptr::copy;
+
+use crate::{LenType, StackBase, Storage};
+
+///
+/// A cursor over a [`Stack`](crate::Stack) that can edit it at any position
+///
+/// The cursor sits on one of the elements of the stack, or past the end of
+/// it, where there is no current element. It is created by
+/// [`StackBase::cursor_mut()`].
+///
+/// By default, every edit shifts the elements after the cursor immediately,
+/// just like [`StackBase::insert()`] and [`StackBase::remove()`], costing O(n) each.
+/// After calling [`Self::batch()`], the cursor instead moves every element
+/// after it to the end of the buffer, leaving a gap at the cursor that
+/// edits and moves can use in O(1). The elements are shifted back once,
+/// either by [`Self::flush()`] or when the cursor is dropped, so a run of
+/// edits costs O(n) in total.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{Stack, stack};
+/// let mut s = stack![1, 2, 3, 4, 5; 10];
+///
+/// let mut cursor = s.cursor_mut(0);
+/// cursor.batch();
+/// while let Some(x) = cursor.current().copied() {
+///     if x % 2 == 0 {
+///         //double up even numbers
+///         cursor.insert_before(x).unwrap();
+///         cursor.move_next();
+///     } else {
+///         //remove odd numbers
+///         cursor.remove_current();
+///     }
+/// }
+/// drop(cursor);
+///
+/// assert_eq!(s, [2, 2, 4, 4]);
+/// ```
+///
+pub struct CursorMut<'a, T, S:?Sized+Storage<T>, L:LenType = usize> {
+    stack: &'a mut StackBase<T,S,L>,
+    index: usize,
+
+    //when batching, the elements from the cursor onward are stored in
+    //`tail..capacity` and the stack's len is kept at `index`
+    tail: Option<usize>
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> Drop for CursorMut<'_,T,S,L> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<T:Debug, S:?Sized+Storage<T>, L:LenType> Debug for CursorMut<'_,T,S,L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("CursorMut")
+            .field("index", &self.index)
+            .field("current", &self.current())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> StackBase<T,S,L> {
+
+    ///
+    /// Creates a cursor sitting on the element at `at`
+    ///
+    /// If `at` is equal to the length of the stack, the cursor starts past
+    /// the end of it
+    ///
+    /// # Panics
+    /// Panics if `at` is greater than the length of the stack
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8; 5];
+    ///
+    /// let mut cursor = s.cursor_mut(1);
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// assert_eq!(cursor.insert_after(3), Ok(()));
+    /// assert_eq!(cursor.replace(1), Ok(2));
+    /// assert!(cursor.move_prev());
+    /// assert_eq!(cursor.remove_current(), Some(6));
+    /// drop(cursor);
+    ///
+    /// assert_eq!(s, [1, 3, 8]);
+    /// ```
+    ///
+    pub fn cursor_mut(&mut self, at: usize) -> CursorMut<'_,T,S,L> {
+        if at > self.len() {
+            panic!("Attempted to place a cursor at {at}, but the len was {}", self.len());
+        }
+        CursorMut { stack: self, index: at, tail: None }
+    }
+
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> CursorMut<'_,T,S,L> {
+
+    fn slot(&mut self, i: usize) -> *mut MaybeUninit<T> {
+        unsafe { self.stack.buf_mut().as_mut_ptr().add(i) }
+    }
+
+    /// The total quantity of elements in the stack
+    pub fn len(&self) -> usize {
+        match self.tail {
+            Some(tail) => self.index + (self.capacity() - tail),
+            None => self.stack.len()
+        }
+    }
+
+    /// `true` when the stack contains no elements
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// The total quantity of values that the stack can hold
+    pub fn capacity(&self) -> usize { self.stack.capacity() }
+
+    /// `true` when the stack contains as many elements as the capacity
+    pub fn is_full(&self) -> bool { self.len() >= self.capacity() }
+
+    ///
+    /// The position of the cursor in the stack
+    ///
+    /// This is equal to the length of the stack when the cursor is past the end
+    ///
+    pub fn index(&self) -> usize { self.index }
+
+    /// `true` if the cursor is currently in batched mode
+    pub fn is_batched(&self) -> bool { self.tail.is_some() }
+
+    ///
+    /// Switches the cursor to batched mode
+    ///
+    /// This moves all elements after the cursor to the end of the buffer so
+    /// that the following edits and moves don't need to shift anything.
+    ///
+    pub fn batch(&mut self) {
+        if self.tail.is_some() { return; }
+        let count = self.stack.len() - self.index;
+        let (src, dest) = (self.slot(self.index), self.slot(self.capacity() - count));
+        unsafe {
+            copy(src, dest, count);
+            self.stack.set_len(self.index);
+        }
+        self.tail = Some(self.capacity() - count);
+    }
+
+    ///
+    /// Shifts the elements after the cursor back into place, switching the
+    /// cursor back to immediate mode
+    ///
+    /// This is called automatically when the cursor is dropped.
+    ///
+    pub fn flush(&mut self) {
+        if let Some(tail) = self.tail.take() {
+            let count = self.capacity() - tail;
+            let (src, dest) = (self.slot(tail), self.slot(self.index));
+            unsafe {
+                copy(src, dest, count);
+                self.stack.set_len(self.index + count);
+            }
+        }
+    }
+
+    /// Returns a reference to the element the cursor is on
+    pub fn current(&self) -> Option<&T> {
+        match self.tail {
+            Some(tail) if tail < self.capacity() => unsafe { Some(self.stack.buf()[tail].assume_init_ref()) },
+            Some(_) => None,
+            None => self.stack.get(self.index)
+        }
+    }
+
+    /// Returns a mutable reference to the element the cursor is on
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        match self.tail {
+            Some(tail) if tail < self.capacity() => unsafe { Some(self.stack.buf_mut()[tail].assume_init_mut()) },
+            Some(_) => None,
+            None => self.stack.get_mut(self.index)
+        }
+    }
+
+    /// Returns a reference to the element just before the cursor
+    pub fn peek_prev(&self) -> Option<&T> {
+        let i = self.index.checked_sub(1)?;
+        match self.tail {
+            Some(_) => unsafe { Some(self.stack.buf()[i].assume_init_ref()) },
+            None => self.stack.get(i)
+        }
+    }
+
+    /// Returns a reference to the element just after the current one
+    pub fn peek_next(&self) -> Option<&T> {
+        match self.tail {
+            Some(tail) if tail + 1 < self.capacity() => unsafe { Some(self.stack.buf()[tail+1].assume_init_ref()) },
+            Some(_) => None,
+            None => self.stack.get(self.index + 1)
+        }
+    }
+
+    ///
+    /// Moves the cursor to the next element
+    ///
+    /// Returns `false` and leaves the cursor in place if it is already past
+    /// the end of the stack
+    ///
+    pub fn move_next(&mut self) -> bool {
+        match self.tail {
+            Some(tail) if tail < self.capacity() => unsafe {
+                let (src, dest) = (self.slot(tail), self.slot(self.index));
+                copy(src, dest, 1);
+                self.index += 1;
+                self.stack.set_len(self.index);
+                self.tail = Some(tail + 1);
+                true
+            },
+            Some(_) => false,
+            None if self.index < self.stack.len() => {
+                self.index += 1;
+                true
+            },
+            None => false
+        }
+    }
+
+    ///
+    /// Moves the cursor to the previous element
+    ///
+    /// Returns `false` and leaves the cursor in place if it is already at
+    /// the start of the stack
+    ///
+    pub fn move_prev(&mut self) -> bool {
+        if self.index == 0 { return false; }
+        self.index -= 1;
+        if let Some(tail) = self.tail {
+            unsafe {
+                let (src, dest) = (self.slot(self.index), self.slot(tail - 1));
+                copy(src, dest, 1);
+                self.stack.set_len(self.index);
+            }
+            self.tail = Some(tail - 1);
+        }
+        true
+    }
+
+    ///
+    /// Inserts an element just before the cursor
+    ///
+    /// The cursor stays on the same element. If the stack is full, the
+    /// value is returned in an `Err()`
+    ///
+    pub fn insert_before(&mut self, x:T) -> Result<(),T> {
+        if self.is_full() { return Err(x); }
+        match self.tail {
+            Some(_) => unsafe {
+                (*self.slot(self.index)).write(x);
+                self.stack.set_len(self.index + 1);
+            },
+            None => { self.stack.insert(self.index, x); }
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    ///
+    /// Inserts an element just after the current one
+    ///
+    /// The cursor stays on the same element. If the cursor is past the end,
+    /// the element is inserted at the end, the same as [`Self::insert_before()`].
+    /// If the stack is full, the value is returned in an `Err()`
+    ///
+    pub fn insert_after(&mut self, x:T) -> Result<(),T> {
+        if self.current().is_none() { return self.insert_before(x); }
+        if self.is_full() { return Err(x); }
+        match self.tail {
+            Some(tail) => unsafe {
+                //shift the current element down into the gap and put `x` after it
+                let (src, dest) = (self.slot(tail), self.slot(tail - 1));
+                copy(src, dest, 1);
+                (*self.slot(tail)).write(x);
+                self.tail = Some(tail - 1);
+            },
+            None => { self.stack.insert(self.index + 1, x); }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Removes the current element and returns it
+    ///
+    /// The cursor moves onto the following element. If the cursor is past
+    /// the end, `None` is returned instead.
+    ///
+    pub fn remove_current(&mut self) -> Option<T> {
+        match self.tail {
+            Some(tail) if tail < self.capacity() => unsafe {
+                self.tail = Some(tail + 1);
+                Some((*self.slot(tail)).assume_init_read())
+            },
+            Some(_) => None,
+            None if self.index < self.stack.len() => Some(self.stack.remove(self.index)),
+            None => None
+        }
+    }
+
+    ///
+    /// Replaces the current element, returning the old one
+    ///
+    /// If the cursor is past the end, the value is returned in an `Err()`
+    ///
+    pub fn replace(&mut self, x:T) -> Result<T,T> {
+        match self.current_mut() {
+            Some(current) => Ok(replace(current, x)),
+            None => Err(x)
+        }
+    }
+
+}
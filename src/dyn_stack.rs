@@ -0,0 +1,534 @@
+//!
+//! A stack of differently-typed values stored inline behind a common `dyn Trait`
+//!
+//! Calling a `dyn FnOnce()` requires moving it out by value, which isn't
+//! possible for unsized values on stable Rust. Instead, a popped
+//! `dyn FnOnce()` can be called with [`Popped::call_once()`], which moves it
+//! out as its original type.
+//!
+
+use core::any::Any;
+use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of, size_of_val, ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, drop_in_place};
+
+///
+/// Conversion from a pointer to a sized value into a pointer to an unsized
+/// type (usually a `dyn Trait`) that the value can be coerced into
+///
+/// This is what allows a [`DynStack`] to remember how to view each of its
+/// values as `D`. Implementations for user traits are best created using
+/// [`impl_dyn_coerce!`](crate::impl_dyn_coerce).
+///
+/// # Safety
+/// [`Coerce::coerce()`] must return the same pointer it was given with
+/// only the metadata added. ie, it must be equivalent to `|ptr| ptr`.
+/// The hidden `call_once()` method must not be overridden
+///
+pub unsafe trait Coerce<T> {
+    /// Adds the metadata needed to view `ptr` as a `Self`
+    fn coerce(ptr: *mut T) -> *mut Self;
+
+    /// Moves the value out of `ptr` and calls it, writing the result to `out`.
+    /// Only overridden by the `dyn FnOnce` types implementing [`DynFnOnce`]
+    #[doc(hidden)]
+    unsafe fn call_once(_ptr: *mut T, _out: *mut ()) {
+        unreachable!("only `dyn FnOnce` values can be called by value")
+    }
+}
+
+unsafe impl<T> Coerce<T> for T {
+    fn coerce(ptr: *mut T) -> *mut T { ptr }
+}
+
+unsafe impl<T, const N:usize> Coerce<[T;N]> for [T] {
+    fn coerce(ptr: *mut [T;N]) -> *mut [T] { ptr }
+}
+
+///
+/// Implements [`Coerce`] from every type implementing the given trait bounds
+/// into the corresponding `dyn` type
+///
+/// Since trait objects of local traits count as local types, this can be
+/// used on traits from the current crate.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{DynStack, impl_dyn_coerce};
+/// trait Command {
+///     fn run(&mut self) -> u32;
+/// }
+///
+/// impl_dyn_coerce!(Command);
+///
+/// struct Add(u32, u32);
+/// impl Command for Add {
+///     fn run(&mut self) -> u32 { self.0 + self.1 }
+/// }
+///
+/// struct Const(u32);
+/// impl Command for Const {
+///     fn run(&mut self) -> u32 { self.0 }
+/// }
+///
+/// let mut commands = DynStack::<dyn Command, 64>::new();
+/// commands.push(Add(6, 2)).ok();
+/// commands.push(Const(8)).ok();
+///
+/// let results: Vec<_> = commands.iter_mut().map(|c| c.run()).collect();
+/// assert_eq!(results, [8, 8]);
+/// ```
+///
+#[macro_export]
+macro_rules! impl_dyn_coerce {
+    ($($bounds:tt)+) => {
+        unsafe impl<__T: $($bounds)+ + 'static> $crate::Coerce<__T> for dyn $($bounds)+ {
+            fn coerce(ptr: *mut __T) -> *mut Self { ptr }
+        }
+    };
+}
+
+impl_dyn_coerce!(Any);
+impl_dyn_coerce!(Any + Send);
+impl_dyn_coerce!(Any + Send + Sync);
+impl_dyn_coerce!(Debug);
+impl_dyn_coerce!(Debug + Send);
+impl_dyn_coerce!(Display);
+impl_dyn_coerce!(Fn());
+impl_dyn_coerce!(FnMut());
+impl_dyn_coerce!(FnMut() + Send);
+
+unsafe impl<R, F:FnOnce() -> R + 'static> Coerce<F> for dyn FnOnce() -> R {
+    fn coerce(ptr: *mut F) -> *mut Self { ptr }
+    unsafe fn call_once(ptr: *mut F, out: *mut ()) {
+        ptr::write(out as *mut R, ptr::read(ptr)())
+    }
+}
+
+unsafe impl<R, F:FnOnce() -> R + Send + 'static> Coerce<F> for dyn FnOnce() -> R + Send {
+    fn coerce(ptr: *mut F) -> *mut Self { ptr }
+    unsafe fn call_once(ptr: *mut F, out: *mut ()) {
+        ptr::write(out as *mut R, ptr::read(ptr)())
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+///
+/// The `dyn FnOnce` types that a [`Popped`] value can be called as, using
+/// [`Popped::call_once()`]
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+///
+pub trait DynFnOnce: sealed::Sealed {
+    /// The return type of the function
+    type Output;
+}
+
+impl<R> sealed::Sealed for dyn FnOnce() -> R {}
+impl<R> DynFnOnce for dyn FnOnce() -> R { type Output = R; }
+
+impl<R> sealed::Sealed for dyn FnOnce() -> R + Send {}
+impl<R> DynFnOnce for dyn FnOnce() -> R + Send { type Output = R; }
+
+/// The maximum alignment of any value stored in a [`DynStack`]
+pub const DYN_STACK_ALIGN: usize = 16;
+
+#[repr(C, align(16))]
+struct Bytes<const BYTES:usize>([MaybeUninit<u8>; BYTES]);
+
+///
+/// Views the value at a pointer as a `D`. When the second pointer isn't null,
+/// the value is also moved out and called, writing its result there
+///
+/// Both are done by one function so that headers only need one pointer.
+///
+type Meta<D> = unsafe fn(*mut u8, *mut ()) -> *mut D;
+
+/// Bookkeeping stored in front of every value
+struct Header<D:?Sized> {
+    meta: Meta<D>,
+    prev: u32,
+    value: u32
+}
+
+impl<D:?Sized> Clone for Header<D> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<D:?Sized> Copy for Header<D> {}
+
+unsafe fn meta_erased<T, D:?Sized+Coerce<T>>(ptr: *mut u8, out: *mut ()) -> *mut D {
+    if !out.is_null() {
+        D::call_once(ptr as *mut T, out);
+    }
+    D::coerce(ptr as *mut T)
+}
+
+const fn align_up(offset: usize, align: usize) -> usize {
+    (offset + align - 1) & !(align - 1)
+}
+
+///
+/// A fixed-capacity stack of values of different types viewed through a
+/// common unsized type `D`, such as `dyn Trait`
+///
+/// Values are stored back-to-back in an inline buffer of `BYTES` bytes along
+/// with the metadata needed to view them as a `D`, so there is no boxing
+/// or dynamic allocation whatsoever. Each value costs its own size plus a
+/// small header and any padding needed to respect its alignment. Values
+/// with an alignment over [`DYN_STACK_ALIGN`] cannot be stored.
+///
+/// Values of type `T` can be pushed whenever `D` implements [`Coerce<T>`].
+/// Common `dyn` types from `core` are covered, and user traits can be
+/// supported using [`impl_dyn_coerce!`](crate::impl_dyn_coerce).
+///
+/// # Examples
+/// ```
+/// # use stack_stack::DynStack;
+/// use core::fmt::Debug;
+///
+/// let mut s = DynStack::<dyn Debug, 128>::new();
+/// assert_eq!(s.push(6u8), Ok(()));
+/// assert_eq!(s.push("two"), Ok(()));
+/// assert_eq!(s.push([8.0f64; 3]), Ok(()));
+///
+/// assert_eq!(s.len(), 3);
+/// assert_eq!(format!("{s:?}"), r#"[6, "two", [8.0, 8.0, 8.0]]"#);
+///
+/// //values that don't fit are given back
+/// assert_eq!(s.push([0u64; 32]), Err([0u64; 32]));
+/// ```
+///
+pub struct DynStack<D:?Sized, const BYTES:usize> {
+    len: usize,
+    top: usize,
+    last: usize,
+    data: Bytes<BYTES>,
+    _marker: PhantomData<D>
+}
+
+impl<D:?Sized, const BYTES:usize> Drop for DynStack<D,BYTES> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<D:?Sized, const BYTES:usize> Default for DynStack<D,BYTES> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<D:?Sized+Debug, const BYTES:usize> Debug for DynStack<D,BYTES> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<D:?Sized, const BYTES:usize> DynStack<D,BYTES> {
+
+    /// Creates an empty stack
+    pub const fn new() -> Self {
+        const { assert!(BYTES <= u32::MAX as usize, "DynStack buffers are limited to u32::MAX bytes") }
+        Self {
+            len: 0, top: 0, last: 0,
+            data: Bytes(unsafe { MaybeUninit::uninit().assume_init() }),
+            _marker: PhantomData
+        }
+    }
+
+    /// The quantity of values in the stack
+    pub const fn len(&self) -> usize { self.len }
+
+    /// `true` when this stack contains no values
+    pub const fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// The quantity of bytes currently in use, including headers and padding
+    pub const fn bytes_used(&self) -> usize { self.top }
+
+    /// The total size of the buffer in bytes. Equivalent to `BYTES`
+    pub const fn capacity_bytes(&self) -> usize { BYTES }
+
+    fn base(&self) -> *const u8 { self.data.0.as_ptr() as *const u8 }
+
+    fn base_mut(&mut self) -> *mut u8 { self.data.0.as_mut_ptr() as *mut u8 }
+
+    unsafe fn header(&self, offset: usize) -> Header<D> {
+        ptr::read(self.base().add(offset) as *const Header<D>)
+    }
+
+    ///
+    /// Adds a value to the top of the stack
+    ///
+    /// If there is not enough room left in the buffer, or if `T` is aligned
+    /// to more than [`DYN_STACK_ALIGN`] bytes, the value is returned in
+    /// an `Err()` and the stack is left unchanged
+    ///
+    pub fn push<T>(&mut self, x:T) -> Result<(),T> where D:Coerce<T> {
+        if align_of::<T>() > DYN_STACK_ALIGN { return Err(x); }
+
+        let header = align_up(self.top, align_of::<Header<D>>());
+        let value = align_up(header + size_of::<Header<D>>(), align_of::<T>());
+        let end = value + size_of::<T>();
+        if end > BYTES { return Err(x); }
+
+        unsafe {
+            let base = self.base_mut();
+            ptr::write(
+                base.add(header) as *mut Header<D>,
+                Header { meta: meta_erased::<T,D>, prev: self.last as u32, value: value as u32 }
+            );
+            ptr::write(base.add(value) as *mut T, x);
+        }
+
+        self.last = header;
+        self.top = end;
+        self.len += 1;
+        Ok(())
+    }
+
+    ///
+    /// Returns a reference to the value at the top of the stack
+    ///
+    pub fn last(&self) -> Option<&D> {
+        if self.is_empty() { return None; }
+        unsafe { Some(&*self.get_raw(self.header(self.last))) }
+    }
+
+    ///
+    /// Returns a mutable reference to the value at the top of the stack
+    ///
+    pub fn last_mut(&mut self) -> Option<&mut D> {
+        if self.is_empty() { return None; }
+        unsafe { Some(&mut *self.get_raw_mut(self.header(self.last))) }
+    }
+
+    unsafe fn get_raw(&self, header: Header<D>) -> *const D {
+        (header.meta)(self.base().add(header.value as usize) as *mut u8, ptr::null_mut())
+    }
+
+    unsafe fn get_raw_mut(&mut self, header: Header<D>) -> *mut D {
+        (header.meta)(self.base_mut().add(header.value as usize), ptr::null_mut())
+    }
+
+    ///
+    /// Removes the value at the top of the stack
+    ///
+    /// The returned guard gives access to the value and drops it once the
+    /// guard itself is dropped. If the stack is empty, `None` is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::DynStack;
+    /// use core::fmt::Display;
+    ///
+    /// let mut s = DynStack::<dyn Display, 64>::new();
+    /// s.push(6).ok();
+    /// s.push('x').ok();
+    ///
+    /// assert_eq!(s.pop().map(|x| x.to_string()).as_deref(), Some("x"));
+    /// assert_eq!(s.pop().map(|x| x.to_string()).as_deref(), Some("6"));
+    /// assert!(s.pop().is_none());
+    /// ```
+    ///
+    pub fn pop(&mut self) -> Option<Popped<'_,D>> {
+        if self.is_empty() { return None; }
+        unsafe {
+            let header = self.header(self.last);
+            self.top = self.last;
+            self.last = header.prev as usize;
+            self.len -= 1;
+            Some(Popped {
+                value: self.get_raw_mut(header),
+                raw: self.base_mut().add(header.value as usize),
+                meta: header.meta,
+                _marker: PhantomData
+            })
+        }
+    }
+
+    ///
+    /// Removes and drops all values after the first `len`
+    ///
+    pub fn truncate(&mut self, len: usize) {
+        while self.len() > len {
+            self.pop();
+        }
+    }
+
+    /// Removes and drops all values in the stack
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Iterates over the values from the bottom of the stack to the top
+    pub fn iter(&self) -> Iter<'_,D> {
+        Iter { base: self.base(), offset: 0, remaining: self.len, _marker: PhantomData }
+    }
+
+    /// Mutably iterates over the values from the bottom of the stack to the top
+    pub fn iter_mut(&mut self) -> IterMut<'_,D> {
+        IterMut { base: self.base_mut(), offset: 0, remaining: self.len, _marker: PhantomData }
+    }
+
+}
+
+impl<'a, D:?Sized, const BYTES:usize> IntoIterator for &'a DynStack<D,BYTES> {
+    type Item = &'a D;
+    type IntoIter = Iter<'a,D>;
+    fn into_iter(self) -> Iter<'a,D> { self.iter() }
+}
+
+impl<'a, D:?Sized, const BYTES:usize> IntoIterator for &'a mut DynStack<D,BYTES> {
+    type Item = &'a mut D;
+    type IntoIter = IterMut<'a,D>;
+    fn into_iter(self) -> IterMut<'a,D> { self.iter_mut() }
+}
+
+///
+/// A value removed from the top of a [`DynStack`]
+///
+/// The value is dropped when this guard is, unless it is a `dyn FnOnce`
+/// consumed by [`Self::call_once()`]
+///
+pub struct Popped<'a, D:?Sized> {
+    value: *mut D,
+    raw: *mut u8,
+    meta: Meta<D>,
+    _marker: PhantomData<&'a mut D>
+}
+
+impl<D:?Sized+DynFnOnce> Popped<'_,D> {
+
+    ///
+    /// Moves the function out and calls it
+    ///
+    /// # Examples
+    /// Every value is dropped exactly once, whether it is called, popped
+    /// and left uncalled, or left in the stack
+    /// ```
+    /// # use stack_stack::dyn_stack::{DynStack, Popped};
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    ///
+    /// struct DropCounter(Rc<Cell<u32>>);
+    /// impl Drop for DropCounter {
+    ///     fn drop(&mut self) { self.0.set(self.0.get() + 1); }
+    /// }
+    ///
+    /// let drops = Rc::new(Cell::new(0));
+    /// let mut s = DynStack::<dyn FnOnce() -> u32, 256>::new();
+    /// for i in 0..4 {
+    ///     let counter = DropCounter(drops.clone());
+    ///     s.push(move || { let _counter = counter; i * 10 }).ok();
+    /// }
+    ///
+    /// //calling a value consumes it
+    /// assert_eq!(s.pop().map(Popped::call_once), Some(30));
+    /// assert_eq!(drops.get(), 1);
+    ///
+    /// //a value that isn't called is dropped with its guard
+    /// drop(s.pop());
+    /// assert_eq!(drops.get(), 2);
+    ///
+    /// //values left in the stack are dropped with it
+    /// drop(s);
+    /// assert_eq!(drops.get(), 4);
+    /// ```
+    ///
+    pub fn call_once(self) -> D::Output {
+        //the value is moved out by the call, so the guard must not drop it
+        let this = ManuallyDrop::new(self);
+        let mut out = MaybeUninit::<D::Output>::uninit();
+        unsafe {
+            (this.meta)(this.raw, out.as_mut_ptr() as *mut ());
+            out.assume_init()
+        }
+    }
+
+}
+
+impl<D:?Sized> Drop for Popped<'_,D> {
+    fn drop(&mut self) {
+        unsafe { drop_in_place(self.value) }
+    }
+}
+
+impl<D:?Sized> Deref for Popped<'_,D> {
+    type Target = D;
+    fn deref(&self) -> &D { unsafe { &*self.value } }
+}
+
+impl<D:?Sized> DerefMut for Popped<'_,D> {
+    fn deref_mut(&mut self) -> &mut D { unsafe { &mut *self.value } }
+}
+
+impl<D:?Sized+Debug> Debug for Popped<'_,D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&**self, f)
+    }
+}
+
+/// An iterator over the values of a [`DynStack`]
+pub struct Iter<'a, D:?Sized> {
+    base: *const u8,
+    offset: usize,
+    remaining: usize,
+    _marker: PhantomData<&'a D>
+}
+
+impl<'a, D:?Sized> Iterator for Iter<'a,D> {
+    type Item = &'a D;
+    fn next(&mut self) -> Option<&'a D> {
+        if self.remaining == 0 { return None; }
+        unsafe {
+            let offset = align_up(self.offset, align_of::<Header<D>>());
+            let header = ptr::read(self.base.add(offset) as *const Header<D>);
+            let value = &*(header.meta)(self.base.add(header.value as usize) as *mut u8, ptr::null_mut());
+            self.offset = header.value as usize + size_of_val(value);
+            self.remaining -= 1;
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<D:?Sized> ExactSizeIterator for Iter<'_,D> {}
+impl<D:?Sized> FusedIterator for Iter<'_,D> {}
+
+/// A mutable iterator over the values of a [`DynStack`]
+pub struct IterMut<'a, D:?Sized> {
+    base: *mut u8,
+    offset: usize,
+    remaining: usize,
+    _marker: PhantomData<&'a mut D>
+}
+
+impl<'a, D:?Sized> Iterator for IterMut<'a,D> {
+    type Item = &'a mut D;
+    fn next(&mut self) -> Option<&'a mut D> {
+        if self.remaining == 0 { return None; }
+        unsafe {
+            let offset = align_up(self.offset, align_of::<Header<D>>());
+            let header = ptr::read(self.base.add(offset) as *const Header<D>);
+            let value = &mut *(header.meta)(self.base.add(header.value as usize), ptr::null_mut());
+            self.offset = header.value as usize + size_of_val(value);
+            self.remaining -= 1;
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<D:?Sized> ExactSizeIterator for IterMut<'_,D> {}
+impl<D:?Sized> FusedIterator for IterMut<'_,D> {}
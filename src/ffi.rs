@@ -0,0 +1,194 @@
+//!
+//! `extern "C"` functions for using stacks from C
+//!
+//! Enabled with the `ffi` feature. The matching declarations are in
+//! `include/stack_stack.h`, which can be used from both C and C++. The
+//! `ffi-test` crate compiles it both ways and links it against these
+//! functions.
+//!
+//! On the C side, a stack of capacity `N` is declared as the struct
+//! `{ size_t len; T data[N]; }`, which has the same layout as a
+//! [`Stack<T, N>`](crate::Stack). The functions take a pointer to it as a
+//! [`RawStack<T>`], which has the buffer as a flexible array member, along
+//! with its capacity, so a single set of functions serves every capacity.
+//!
+//! Functions are provided for the integer and float types, and are named
+//! `stack_stack_{type}_{method}`, such as `stack_stack_u8_push`.
+//!
+//! # Examples
+//! ```
+//! # use stack_stack::{Stack, stack};
+//! use stack_stack::ffi::*;
+//!
+//! let mut s = stack![6, 2; 3];
+//! let raw = RawStack::from_stack(&mut s);
+//!
+//! unsafe {
+//!     assert!(stack_stack_u32_push(raw, 3, 8));
+//!     assert!(!stack_stack_u32_push(raw, 3, 3));
+//!
+//!     let mut top = 0;
+//!     assert!(stack_stack_u32_pop(raw, &mut top));
+//!     assert_eq!(top, 8);
+//! }
+//!
+//! assert_eq!(s, [6, 2]);
+//! ```
+//!
+
+use core::mem::MaybeUninit;
+use core::ptr::slice_from_raw_parts_mut;
+
+use crate::{Stack, StackView};
+
+///
+/// The C view of a [`Stack`] with its capacity erased
+///
+/// This is the header of a `{ size_t len; T data[N]; }` struct, with `data`
+/// as a zero-length array standing in for a C flexible array member. It is
+/// only ever used behind a pointer.
+///
+#[repr(C)]
+pub struct RawStack<T> {
+    /// The quantity of initialized values at the start of `data`
+    pub len: usize,
+    /// The start of the buffer
+    pub data: [T; 0]
+}
+
+impl<T> RawStack<T> {
+
+    /// Casts a pointer to a [`Stack`] into a pointer to its header
+    pub fn from_stack<const N:usize>(stack: &mut Stack<T,N>) -> *mut Self {
+        stack as *mut Stack<T,N> as *mut Self
+    }
+
+    ///
+    /// Turns a pointer to a header back into a [`StackView`]
+    ///
+    /// # Safety
+    /// `ptr` must point to a valid stack with a buffer of at least `capacity`
+    /// items, and the stack must not be accessed through any other pointer
+    /// for the lifetime `'a`
+    ///
+    pub unsafe fn as_view<'a>(ptr: *mut Self, capacity: usize) -> &'a mut StackView<T> {
+        &mut *(slice_from_raw_parts_mut(ptr as *mut MaybeUninit<T>, capacity) as *mut StackView<T>)
+    }
+
+}
+
+macro_rules! ffi_functions {
+    ($($ty:ty => $push:ident, $pop:ident, $insert:ident, $remove:ident, $extend:ident, $clear:ident;)*) => {$(
+
+        ///
+        /// Pushes a value, returning `false` if the stack was full
+        ///
+        /// # Safety
+        /// See [`RawStack::as_view()`]
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $push(stack: *mut RawStack<$ty>, capacity: usize, value: $ty) -> bool {
+            RawStack::as_view(stack, capacity).push(value).is_ok()
+        }
+
+        ///
+        /// Pops a value into `out`, returning `false` if the stack was empty
+        ///
+        /// # Safety
+        /// See [`RawStack::as_view()`]. `out` must be valid for writes.
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $pop(stack: *mut RawStack<$ty>, out: *mut $ty) -> bool {
+            let len = (*stack).len;
+            match RawStack::as_view(stack, len).pop() {
+                Some(x) => { out.write(x); true },
+                None => false
+            }
+        }
+
+        ///
+        /// Inserts a value at an index, returning `false` if the stack was
+        /// full or the index was greater than the length
+        ///
+        /// # Safety
+        /// See [`RawStack::as_view()`]
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $insert(
+            stack: *mut RawStack<$ty>, capacity: usize, index: usize, value: $ty
+        ) -> bool {
+            let stack = RawStack::as_view(stack, capacity);
+            if stack.is_full() || index > stack.len() { return false; }
+            stack.insert(index, value);
+            true
+        }
+
+        ///
+        /// Removes the value at an index into `out`, returning `false` if
+        /// the index was out of bounds
+        ///
+        /// # Safety
+        /// See [`RawStack::as_view()`]. `out` must be valid for writes.
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $remove(stack: *mut RawStack<$ty>, index: usize, out: *mut $ty) -> bool {
+            let len = (*stack).len;
+            if index >= len { return false; }
+            out.write(RawStack::as_view(stack, len).remove(index));
+            true
+        }
+
+        ///
+        /// Pushes as many of `count` values as fit, returning how many were pushed
+        ///
+        /// # Safety
+        /// See [`RawStack::as_view()`]. `values` must be valid for reads of
+        /// `count` items.
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $extend(
+            stack: *mut RawStack<$ty>, capacity: usize, values: *const $ty, count: usize
+        ) -> usize {
+            let values = core::slice::from_raw_parts(values, count);
+            match RawStack::as_view(stack, capacity).extend_from_slice(values) {
+                Ok(()) => count,
+                Err(rest) => count - rest.len()
+            }
+        }
+
+        ///
+        /// Removes every value
+        ///
+        /// # Safety
+        /// `stack` must point to a valid stack
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $clear(stack: *mut RawStack<$ty>) {
+            (*stack).len = 0;
+        }
+
+    )*};
+}
+
+ffi_functions! {
+    u8 => stack_stack_u8_push, stack_stack_u8_pop, stack_stack_u8_insert,
+        stack_stack_u8_remove, stack_stack_u8_extend, stack_stack_u8_clear;
+    u16 => stack_stack_u16_push, stack_stack_u16_pop, stack_stack_u16_insert,
+        stack_stack_u16_remove, stack_stack_u16_extend, stack_stack_u16_clear;
+    u32 => stack_stack_u32_push, stack_stack_u32_pop, stack_stack_u32_insert,
+        stack_stack_u32_remove, stack_stack_u32_extend, stack_stack_u32_clear;
+    u64 => stack_stack_u64_push, stack_stack_u64_pop, stack_stack_u64_insert,
+        stack_stack_u64_remove, stack_stack_u64_extend, stack_stack_u64_clear;
+    i8 => stack_stack_i8_push, stack_stack_i8_pop, stack_stack_i8_insert,
+        stack_stack_i8_remove, stack_stack_i8_extend, stack_stack_i8_clear;
+    i16 => stack_stack_i16_push, stack_stack_i16_pop, stack_stack_i16_insert,
+        stack_stack_i16_remove, stack_stack_i16_extend, stack_stack_i16_clear;
+    i32 => stack_stack_i32_push, stack_stack_i32_pop, stack_stack_i32_insert,
+        stack_stack_i32_remove, stack_stack_i32_extend, stack_stack_i32_clear;
+    i64 => stack_stack_i64_push, stack_stack_i64_pop, stack_stack_i64_insert,
+        stack_stack_i64_remove, stack_stack_i64_extend, stack_stack_i64_clear;
+    f32 => stack_stack_f32_push, stack_stack_f32_pop, stack_stack_f32_insert,
+        stack_stack_f32_remove, stack_stack_f32_extend, stack_stack_f32_clear;
+    f64 => stack_stack_f64_push, stack_stack_f64_pop, stack_stack_f64_insert,
+        stack_stack_f64_remove, stack_stack_f64_extend, stack_stack_f64_clear;
+}
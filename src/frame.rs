@@ -0,0 +1,250 @@
+use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+use crate::Stack;
+
+///
+/// Error returned when a new frame cannot be pushed onto a [`FrameStack`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// The maximum frame depth was reached
+    StackOverflow,
+    /// There was not enough capacity left for the locals of the new frame
+    OutOfCapacity
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::StackOverflow => f.write_str("stack overflow: maximum frame depth reached"),
+            Self::OutOfCapacity => f.write_str("not enough capacity for the frame's locals"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    base: usize,
+    locals: usize
+}
+
+///
+/// A [`Stack`] of values split into call frames, such as the operand stack
+/// of a bytecode interpreter
+///
+/// Each frame starts with a fixed number of local slots and may push
+/// temporaries above them. At most `N` values and `F` frames can be stored.
+/// Values below the current frame can't be popped, but the top of the
+/// caller's frame can be read with [`Self::arg()`].
+///
+/// # Examples
+/// ```
+/// # use stack_stack::FrameStack;
+/// let mut vm = FrameStack::<i32, 16, 4>::new();
+///
+/// //the caller pushes the arguments
+/// vm.push(6).ok();
+/// vm.push(2).ok();
+///
+/// //the callee gets one local and reads its arguments
+/// vm.push_frame(1).unwrap();
+/// vm.locals_mut()[0] = vm.arg(1).unwrap() * vm.arg(0).unwrap();
+/// vm.push(vm.locals()[0] + 1).ok();
+///
+/// //returning drops the whole frame
+/// let ret = vm.pop().unwrap();
+/// vm.pop_frame();
+/// assert_eq!(ret, 13);
+/// assert_eq!(vm.as_slice(), [6, 2]);
+/// ```
+///
+pub struct FrameStack<T, const N:usize, const F:usize = 64> {
+    values: Stack<T,N>,
+    frames: Stack<Frame,F>
+}
+
+impl<T:Clone, const N:usize, const F:usize> Clone for FrameStack<T,N,F> {
+    fn clone(&self) -> Self {
+        Self { values: self.values.clone(), frames: self.frames.clone() }
+    }
+}
+
+impl<T, const N:usize, const F:usize> Default for FrameStack<T,N,F> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T:Debug, const N:usize, const F:usize> Debug for FrameStack<T,N,F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut list = f.debug_list();
+        let mut start = 0;
+        for frame in self.frames.iter() {
+            list.entry(&&self.values[start..frame.base]);
+            start = frame.base;
+        }
+        list.entry(&&self.values[start..]).finish()
+    }
+}
+
+impl<T, const N:usize, const F:usize> FrameStack<T,N,F> {
+
+    /// Creates an empty stack with no frames
+    pub const fn new() -> Self {
+        Self { values: Stack::new(), frames: Stack::new() }
+    }
+
+    /// The quantity of values across all frames
+    pub const fn len(&self) -> usize { self.values.len() }
+
+    /// `true` when there are no values in any frame
+    pub const fn is_empty(&self) -> bool { self.values.is_empty() }
+
+    /// The total quantity of values that this stack can hold.
+    /// Equivalent to `N`
+    pub const fn capacity(&self) -> usize { N }
+
+    /// The quantity of frames currently pushed
+    pub const fn depth(&self) -> usize { self.frames.len() }
+
+    /// The maximum quantity of frames. Equivalent to `F`
+    pub const fn max_depth(&self) -> usize { F }
+
+    /// Returns a slice of every value in the stack, across all frames
+    pub fn as_slice(&self) -> &[T] { self.values.as_slice() }
+
+    fn current(&self) -> Frame {
+        self.frames.last().copied().unwrap_or(Frame { base: 0, locals: 0 })
+    }
+
+    ///
+    /// Starts a new frame with `n_locals` slots initialized using [`Default`]
+    ///
+    /// # Errors
+    /// Returns [`FrameError::StackOverflow`] if the maximum depth has been
+    /// reached or [`FrameError::OutOfCapacity`] if the locals don't fit.
+    /// The stack is left unchanged in either case.
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{FrameStack, FrameError};
+    /// let mut vm = FrameStack::<u8, 4, 2>::new();
+    /// assert_eq!(vm.push_frame(1), Ok(()));
+    /// assert_eq!(vm.push_frame(5), Err(FrameError::OutOfCapacity));
+    /// assert_eq!(vm.push_frame(1), Ok(()));
+    /// assert_eq!(vm.push_frame(1), Err(FrameError::StackOverflow));
+    /// assert_eq!(vm.depth(), 2);
+    /// ```
+    ///
+    pub fn push_frame(&mut self, n_locals: usize) -> Result<(),FrameError> where T:Default {
+        self.push_frame_with(n_locals, T::default)
+    }
+
+    ///
+    /// Starts a new frame with `n_locals` slots initialized by calling `f`
+    ///
+    /// # Errors
+    /// Same as [`Self::push_frame()`]
+    ///
+    pub fn push_frame_with<G:FnMut()->T>(&mut self, n_locals: usize, f:G) -> Result<(),FrameError> {
+        if self.frames.is_full() { return Err(FrameError::StackOverflow); }
+        if N - self.values.len() < n_locals { return Err(FrameError::OutOfCapacity); }
+
+        let base = self.values.len();
+        self.values.resize_with(base + n_locals, f);
+        self.frames.push(Frame { base, locals: n_locals }).ok();
+        Ok(())
+    }
+
+    ///
+    /// Ends the current frame, dropping its locals and any values pushed in it
+    ///
+    /// Returns `false` if there was no frame to end
+    ///
+    pub fn pop_frame(&mut self) -> bool {
+        match self.frames.pop() {
+            Some(frame) => {
+                self.values.truncate(frame.base);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// The local slots of the current frame
+    pub fn locals(&self) -> &[T] {
+        let frame = self.current();
+        &self.values[frame.base..frame.base+frame.locals]
+    }
+
+    /// The local slots of the current frame
+    pub fn locals_mut(&mut self) -> &mut [T] {
+        let frame = self.current();
+        &mut self.values[frame.base..frame.base+frame.locals]
+    }
+
+    /// Every value in the current frame, including its locals
+    pub fn frame(&self) -> &[T] {
+        &self.values[self.current().base..]
+    }
+
+    /// Every value in the current frame, including its locals
+    pub fn frame_mut(&mut self) -> &mut [T] {
+        let base = self.current().base;
+        &mut self.values[base..]
+    }
+
+    fn arg_index(&self, i: usize) -> Option<usize> {
+        let depth = self.frames.len();
+        if depth == 0 { return None; }
+        let base = self.frames[depth-1].base;
+        let caller = if depth > 1 { self.frames[depth-2].base } else { 0 };
+        if i < base - caller { Some(base - 1 - i) } else { None }
+    }
+
+    ///
+    /// Reads a value from the top of the caller's frame
+    ///
+    /// `arg(0)` is the last value the caller pushed before the current frame
+    /// started, `arg(1)` the one before that, and so on. Returns `None` if
+    /// there is no such value in the caller's frame.
+    ///
+    pub fn arg(&self, i: usize) -> Option<&T> {
+        self.arg_index(i).map(|i| &self.values[i])
+    }
+
+    /// Mutable version of [`Self::arg()`]
+    pub fn arg_mut(&mut self, i: usize) -> Option<&mut T> {
+        self.arg_index(i).map(|i| &mut self.values[i])
+    }
+
+    ///
+    /// Pushes a value onto the current frame
+    ///
+    /// If the stack is full, the value is returned in an `Err()`
+    ///
+    pub fn push(&mut self, x:T) -> Result<(),T> {
+        self.values.push(x)
+    }
+
+    ///
+    /// Pops a value from the current frame
+    ///
+    /// Returns `None` once only the frame's locals remain
+    ///
+    pub fn pop(&mut self) -> Option<T> {
+        let frame = self.current();
+        if self.values.len() > frame.base + frame.locals {
+            self.values.pop()
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Drops every value and frame
+    ///
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.frames.clear();
+    }
+
+}
@@ -0,0 +1,35 @@
+use core::mem::size_of;
+
+use arbitrary::{size_hint, Arbitrary, Result, Unstructured};
+
+use crate::{LenType, Stack};
+
+///
+/// Generates a stack with a length anywhere from `0` to `N`, with the
+/// `arbitrary` feature
+///
+/// # Examples
+/// ```
+/// # use stack_stack::Stack;
+/// use arbitrary::{Arbitrary, Unstructured};
+///
+/// let mut u = Unstructured::new(&[6, 2, 8, 3, 1]);
+/// let s = Stack::<u8, 4>::arbitrary(&mut u).unwrap();
+/// assert!(s.len() <= 4);
+/// ```
+///
+impl<'a, T:Arbitrary<'a>, const N:usize, L:LenType> Arbitrary<'a> for Stack<T,N,L> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let len = u.int_in_range(0..=N)?;
+        let mut stack = Self::new();
+        for _ in 0..len {
+            stack.push(T::arbitrary(u)?).ok();
+        }
+        Ok(stack)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        let (_, upper) = T::size_hint(depth);
+        size_hint::and((0, Some(size_of::<usize>())), (0, upper.and_then(|x| x.checked_mul(N))))
+    }
+}
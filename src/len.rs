@@ -0,0 +1,109 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use core::mem::size_of;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+///
+/// An unsigned integer type that can store the length of a [`Stack`](crate::Stack)
+///
+/// Implemented for `u8`, `u16`, `u32` and `usize`. Using a smaller type than
+/// `usize` makes the stack itself smaller, which matters most for stacks of
+/// small items like `Stack<u8, 16>`, where the length would otherwise take a
+/// third of the space. A stack's capacity must fit in its length type, which
+/// is checked at compile time.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::Stack;
+/// use core::mem::size_of;
+///
+/// assert_eq!(size_of::<Stack<u8, 16, u8>>(), 17);
+/// assert_eq!(size_of::<Stack<u8, 16>>(), 16 + size_of::<usize>());
+///
+/// let mut s: Stack<u8, 16, u8> = Stack::new();
+/// s.extend_from_slice(&[6, 2, 8]).ok();
+/// assert_eq!(s, [6, 2, 8]);
+/// ```
+///
+/// ```compile_fail
+/// # use stack_stack::Stack;
+/// let s: Stack<u8, 256, u8> = Stack::new();
+/// ```
+///
+pub trait LenType: sealed::Sealed + Copy + Debug + Eq + Ord + Hash + Send + Sync + 'static {
+    /// Zero, the length of an empty stack
+    const ZERO: Self;
+
+    /// The largest length that this type can store
+    const MAX: usize;
+
+    /// Converts a length into a `usize`
+    fn to_usize(self) -> usize;
+
+    /// Converts a `usize` into a length, truncating it if it doesn't fit
+    fn from_usize(len: usize) -> Self;
+}
+
+macro_rules! impl_len_type {
+    ($($ty:ty),*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl LenType for $ty {
+            const ZERO: Self = 0;
+            const MAX: usize = if <$ty>::MAX as u128 > usize::MAX as u128 {
+                usize::MAX
+            } else {
+                <$ty>::MAX as usize
+            };
+
+            #[inline]
+            fn to_usize(self) -> usize { self as usize }
+
+            #[inline]
+            fn from_usize(len: usize) -> Self { len as Self }
+        }
+    )*};
+}
+
+impl_len_type!(u8, u16, u32, usize);
+
+///
+/// Reads a length as a `usize` in a `const` context, where trait methods
+/// can't be called
+///
+/// Since the trait is sealed, the size of `L` is enough to tell which
+/// unsigned integer it is.
+///
+#[inline]
+pub(crate) const fn len_to_usize<L:LenType>(len: &L) -> usize {
+    let ptr = len as *const L;
+    unsafe {
+        match size_of::<L>() {
+            1 => *(ptr as *const u8) as usize,
+            2 => *(ptr as *const u16) as usize,
+            4 => *(ptr as *const u32) as usize,
+            _ => *(ptr as *const usize),
+        }
+    }
+}
+
+///
+/// Converts a `usize` into a length in a `const` context, truncating it if
+/// it doesn't fit
+///
+#[inline]
+pub(crate) const fn len_from_usize<L:LenType>(len: usize) -> L {
+    unsafe {
+        match size_of::<L>() {
+            1 => *(&(len as u8) as *const u8 as *const L),
+            2 => *(&(len as u16) as *const u16 as *const L),
+            4 => *(&(len as u32) as *const u32 as *const L),
+            _ => *(&len as *const usize as *const L),
+        }
+    }
+}
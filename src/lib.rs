@@ -1,5 +1,7 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
+#![cfg_attr(feature = "nightly", feature(trusted_len))]
+#![allow(clippy::partialeq_ne_impl)]
 
 use core::iter::*;
 use core::mem::*;
@@ -7,14 +9,74 @@ use core::ops::*;
 use core::slice::*;
 use core::borrow::*;
 use core::hash::*;
-use core::ptr::copy_nonoverlapping;
+use core::ptr::{copy, copy_nonoverlapping, drop_in_place};
+use core::marker::PhantomData;
 use core::fmt::{Debug, Formatter, Result as FmtResult};
 
+use len::len_to_usize;
+
+pub mod spsc;
+pub mod channel;
+pub mod dyn_stack;
+pub mod arena;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "rkyv")]
+mod archive;
+mod bytes;
+mod c_string;
+mod checkpoint;
+mod copy_stack;
+mod cursor;
+mod frame;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod len;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "bytemuck")]
+mod pod;
+mod storage;
+mod string;
+
+pub use dyn_stack::{Coerce, DynFnOnce, DynStack};
+pub use arena::StackArena;
+#[cfg(feature = "rkyv")]
+pub use archive::ArchivedStack;
+pub use bytes::{ByteStack, ByteReader, CapacityError, DecodeError, Encode, Decode};
+pub use c_string::{StackCString, CStringError};
+pub use checkpoint::Checkpoint;
+pub use copy_stack::{AsStack, CopyStack};
+pub use cursor::CursorMut;
+pub use frame::{FrameError, FrameStack};
+pub use len::LenType;
+#[cfg(feature = "rayon")]
+pub use par::IntoParIter;
+#[cfg(feature = "bytemuck")]
+pub use pod::FromBytesError;
+pub use storage::{SliceStack, Storage, Aligned, AlignedStack, Align16, Align32, Align64, Align128};
+pub use string::StackString;
+
 ///
 /// Utility macro for creating a stack from values
 /// 
+/// The following forms are accepted:
+/// - `stack![a, b, c]` creates a full stack with a capacity equal to the
+///   quantity of values
+/// - `stack![a, b, c; cap]` creates a stack of the values with the given
+///   capacity. Giving more values than the capacity is a compile-time error.
+/// - `stack![x; n; cap]` creates a stack of `n` clones of `x`
+/// - `stack![|| f(); n; cap]` creates a stack of `n` values returned by
+///   calling the closure, so `Clone` isn't required
+/// - `stack![iter => cap]` creates a stack from the values of an iterator
+/// 
+/// See [`try_stack!`] for a version that doesn't panic.
+/// 
 /// # Panics
 /// Panics if the capacity provided is less than the quantity of values
+/// in the repeating and iterator forms
 /// 
 /// # Examples
 /// 
@@ -28,48 +90,303 @@ use core::fmt::{Debug, Formatter, Result as FmtResult};
 /// assert_eq!(s2, [3,3,3,3]);
 /// assert_eq!(s2.capacity(), 5);
 /// 
+/// let s3 = stack![6,2,8];
+/// assert_eq!(s3, [6,2,8]);
+/// assert_eq!(s3.capacity(), 3);
+/// 
+/// let mut n = 0;
+/// let s4 = stack![|| { n += 1; n }; 3; 5];
+/// assert_eq!(s4, [1,2,3]);
+/// 
+/// let s5 = stack![(0..4).map(|x| x*x) => 5];
+/// assert_eq!(s5, [0,1,4,9]);
+/// assert_eq!(s5.capacity(), 5);
+/// 
+/// ```
+/// 
+/// ```compile_fail
+/// # use stack_stack::{Stack, stack};
+/// let s1 = stack![6,2,8,3,1; 3];
 /// ```
 /// 
 #[macro_export]
 macro_rules! stack {
 
+    (@unit $x:expr) => { () };
+
+    (|| $f:expr; $n:expr; $cap:expr) => {
+        {
+            let mut stack = $crate::Stack::with_capacity::<$cap>();
+            stack.resize_with($n, || $f);
+            stack
+        }
+    };
+
+    (move || $f:expr; $n:expr; $cap:expr) => {
+        {
+            let mut stack = $crate::Stack::with_capacity::<$cap>();
+            stack.resize_with($n, move || $f);
+            stack
+        }
+    };
+
     ($elem:expr; $n:expr; $cap:expr) => {
         {
-            let mut stack = Stack::with_capacity::<$cap>();
+            let mut stack = $crate::Stack::with_capacity::<$cap>();
             stack.resize($n, $elem);
             stack
         }
     };
 
+    ($iter:expr => $cap:expr) => {
+        match $crate::try_stack![$iter => $cap] {
+            Ok(stack) => stack,
+            Err(_) => panic!(
+                "Attempted to create a stack from an iterator, but the capacity was {}",
+                $cap
+            )
+        }
+    };
+
     ($($x:expr),+ $(,)?; $cap:expr) => {
         {
-            let vals = [$($x),*];
-            let mut stack = Stack::with_capacity::<$cap>();
-            if stack.extend_from_slice(&vals).is_err() {
-                panic!(
-                    "Attempted to create a stack of len {}, but the capacity was {}",
-                    vals.len(), $cap
+            const {
+                assert!(
+                    <[()]>::len(&[$($crate::stack!(@unit $x)),+]) <= $cap,
+                    "Attempted to create a stack with more values than its capacity"
                 )
-            }
-            stack
+            };
+            $crate::Stack::from_array([$($x),+]).resize_capacity::<$cap>()
         }
-    }
+    };
+
+    ($($x:expr),+ $(,)?) => {
+        $crate::Stack::from_array([$($x),+])
+    };
+
+}
+
+///
+/// Fallible version of [`stack!`] that returns a `Result` instead of
+/// failing when the values don't fit in the capacity
+/// 
+/// - `try_stack![a, b, c; cap]` returns the values in a full stack
+///   inside the `Err()` if there are too many of them
+/// - `try_stack![iter => cap]` returns the iterator inside the `Err()`
+///   if it has more items than the capacity, after filling a stack with
+///   as many as possible
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// # use stack_stack::{Stack, try_stack};
+/// let s1 = try_stack![6,2,8; 5].unwrap();
+/// assert_eq!(s1, [6,2,8]);
+/// assert_eq!(s1.capacity(), 5);
+/// 
+/// let s2 = try_stack![6,2,8,3,1; 3];
+/// assert_eq!(s2.unwrap_err().capacity(), 5);
+/// 
+/// let s3 = try_stack![0..3 => 3].unwrap();
+/// assert_eq!(s3, [0,1,2]);
+/// 
+/// let mut rest = try_stack![0..10 => 3].unwrap_err();
+/// assert_eq!(rest.next(), Some(3));
+/// 
+/// ```
+/// 
+#[macro_export]
+macro_rules! try_stack {
+
+    ($iter:expr => $cap:expr) => {
+        {
+            let mut stack = $crate::Stack::with_capacity::<$cap>();
+            let mut iter = ::core::iter::IntoIterator::into_iter($iter).peekable();
+            let full = stack.extend_from_iter(iter.by_ref()).is_err();
+            if full && iter.peek().is_some() { Err(iter) } else { Ok(stack) }
+        }
+    };
+
+    ($($x:expr),+ $(,)?; $cap:expr) => {
+        $crate::Stack::from_array([$($x),+]).try_resize_capacity::<$cap>()
+    };
+
+}
+
+///
+/// Formats text into a [`StackString`] with the given capacity, like
+/// `format!()` without allocating
+/// 
+/// The following forms are accepted:
+/// - `stack_format!(cap; "{}", x)` panics if the text doesn't fit
+/// - `stack_format!(cap, truncate; "{}", x)` cuts the text short at a char
+///   boundary if it doesn't fit
+/// - `stack_format!(cap, truncate = "…"; "{}", x)` cuts the text short and
+///   ends it with the marker if it doesn't fit
+/// 
+/// See [`try_stack_format!`] for a version that returns an error instead.
+/// 
+/// # Panics
+/// Panics if the text doesn't fit in the capacity in the first form
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// # use stack_stack::stack_format;
+/// let s1 = stack_format!(16; "{}:{}", "sensor", 3);
+/// assert_eq!(s1, "sensor:3");
+/// assert_eq!(s1.capacity(), 16);
+/// 
+/// let s2 = stack_format!(8, truncate; "{}", "température");
+/// assert_eq!(s2, "tempéra");
+/// 
+/// let s3 = stack_format!(8, truncate = "…"; "{}", "température");
+/// assert_eq!(s3, "temp…");
+/// ```
+/// 
+/// ```should_panic
+/// # use stack_stack::stack_format;
+/// let s = stack_format!(4; "{}", 123456);
+/// ```
+/// 
+#[macro_export]
+macro_rules! stack_format {
+
+    ($cap:expr, truncate; $($arg:tt)+) => {
+        $crate::StackString::<$cap>::from_fmt_truncated(::core::format_args!($($arg)+), "")
+    };
+
+    ($cap:expr, truncate = $marker:expr; $($arg:tt)+) => {
+        $crate::StackString::<$cap>::from_fmt_truncated(::core::format_args!($($arg)+), $marker)
+    };
+
+    ($cap:expr; $($arg:tt)+) => {
+        match $crate::try_stack_format!($cap; $($arg)+) {
+            Ok(s) => s,
+            Err(_) => panic!(
+                "Attempted to format a string, but the capacity was {}",
+                $cap
+            )
+        }
+    };
+
+}
 
+///
+/// Fallible version of [`stack_format!`] that returns a `Result` instead of
+/// failing when the text doesn't fit in the capacity
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// # use stack_stack::try_stack_format;
+/// let s = try_stack_format!(8; "{:.2}", 1.0 / 3.0).unwrap();
+/// assert_eq!(s, "0.33");
+/// 
+/// assert!(try_stack_format!(4; "{}", u32::MAX).is_err());
+/// ```
+/// 
+#[macro_export]
+macro_rules! try_stack_format {
+
+    ($cap:expr; $($arg:tt)+) => {
+        $crate::StackString::<$cap>::from_fmt(::core::format_args!($($arg)+))
+    };
+
+}
+
+///
+/// A fixed-capacity stack over any kind of [`Storage`]
+/// 
+/// This holds the implementation shared by every kind of stack. Most of the
+/// time, it is used through [`Stack`], which stores its values in an array,
+/// or [`SliceStack`], which stores them in a borrowed buffer.
+/// 
+/// The length is stored as an `L`, which can be set to a smaller integer
+/// type than `usize` to save space. See [`LenType`] for details.
+/// 
+/// # Layout
+/// The layout is `#[repr(C)]` and guarranteed to stay that way. The length
+/// is always at offset 0 and the buffer follows it at the first offset
+/// aligned for the storage, just like the C struct `{ L len; T data[N]; }`.
+/// Only the first `len` items of the buffer are initialized.
+/// 
+/// ```
+/// # use stack_stack::{Stack, AlignedStack, Align32};
+/// use core::mem::{size_of, align_of};
+/// 
+/// fn data_offset<T, const N:usize, L:stack_stack::LenType>(s: &Stack<T,N,L>) -> usize {
+///     s.as_ptr() as usize - s as *const _ as usize
+/// }
+/// 
+/// let s1: Stack<u8, 5> = Stack::new();
+/// assert_eq!(data_offset(&s1), size_of::<usize>());
+/// assert_eq!(size_of::<Stack<u8, 5>>(), 2 * size_of::<usize>());
+/// 
+/// let s2: Stack<u8, 5, u8> = Stack::new();
+/// assert_eq!(data_offset(&s2), 1);
+/// assert_eq!(size_of::<Stack<u8, 5, u8>>(), 6);
+/// 
+/// let s3: Stack<u32, 5, u16> = Stack::new();
+/// assert_eq!(data_offset(&s3), 4);
+/// assert_eq!(size_of::<Stack<u32, 5, u16>>(), 24);
+/// 
+/// let s4: Stack<u64, 2, u8> = Stack::new();
+/// assert_eq!(data_offset(&s4), align_of::<u64>());
+/// 
+/// let s5: AlignedStack<u8, 5, Align32, u8> = AlignedStack::new();
+/// assert_eq!(s5.as_ptr() as usize - &s5 as *const _ as usize, 32);
+/// assert_eq!(size_of::<AlignedStack<u8, 5, Align32, u8>>(), 64);
+/// ```
+/// 
+#[repr(C)]
+pub struct StackBase<T, S:?Sized+Storage<T>, L:LenType = usize> {
+    len: L,
+    _marker: PhantomData<T>,
+    data: S
 }
 
 ///
 /// A basic fixed-capacity stack stored statically
 /// 
 /// The design of its methods is based pretty closely on `Vec`, with
-/// the primary difference being that [`push`](Self::push()) returns an [`Option`]
+/// the primary difference being that [`push`](StackBase::push()) returns an [`Option`]
 /// containing the pushed value if `self` is at capacity.
 /// 
-pub struct Stack<T, const N:usize> {
-    len: usize,
-    data: [MaybeUninit<T>; N]
-}
+pub type Stack<T, const N:usize, L = usize> = StackBase<T, [MaybeUninit<T>; N], L>;
+
+///
+/// A stack with its capacity erased from its type
+/// 
+/// Any [`Stack`] can be unsized into a view, the same way that an array can
+/// be unsized into a slice. This allows a single non-generic function to
+/// work with stacks of every capacity, without a monomorphized copy for each.
+/// The view has the entire API of [`StackBase`] that doesn't need to move
+/// the stack, including all of its mutating methods.
+/// 
+/// # Examples
+/// ```
+/// # use stack_stack::{Stack, StackView, stack};
+/// fn push_squares(s: &mut StackView<u32>) -> Result<(), u32> {
+///     s.retain(|x| x % 2 == 0);
+///     for i in 0..s.len() as u32 {
+///         s.push(i * i)?;
+///     }
+///     Ok(())
+/// }
+/// 
+/// let mut s1 = stack![6, 2, 8; 10];
+/// let mut s2 = stack![6, 2, 8; 4];
+/// 
+/// assert_eq!(push_squares(&mut s1), Ok(()));
+/// assert_eq!(push_squares(&mut s2), Err(1));
+/// assert_eq!(s1, [6, 2, 8, 0, 1, 4]);
+/// assert_eq!(s2, [6, 2, 8, 0]);
+/// ```
+/// 
+pub type StackView<T, L = usize> = StackBase<T, [MaybeUninit<T>], L>;
 
-impl<T:Clone, const N:usize> Clone for Stack<T, N> {
+impl<T:Clone, const N:usize, L:LenType> Clone for Stack<T,N,L> {
     fn clone(&self) -> Self {
         let mut new = Stack::new();
         while new.len() < self.len() {
@@ -79,26 +396,26 @@ impl<T:Clone, const N:usize> Clone for Stack<T, N> {
     }
 }
 
-impl<T, const N:usize> Drop for Stack<T, N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> Drop for StackBase<T,S,L> {
     fn drop(&mut self) {
         self.clear();
     }
 }
 
-impl<T, const N:usize> Deref for Stack<T,N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> Deref for StackBase<T,S,L> {
     type Target = [T];
     fn deref(&self) -> &[T] {
         self.as_slice()
     }
 }
 
-impl<T, const N:usize> DerefMut for Stack<T,N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> DerefMut for StackBase<T,S,L> {
     fn deref_mut(&mut self) -> &mut [T] {
         self.as_mut_slice()
     }
 }
 
-impl<T, const N:usize> Default for Stack<T,N> {
+impl<T, const N:usize, L:LenType> Default for Stack<T,N,L> {
     fn default() -> Self { Self::new() }
 }
 
@@ -106,89 +423,101 @@ impl<T, const N:usize> From<[T;N]> for Stack<T,N> {
     fn from(array: [T;N]) -> Self { Self::from_array(array) }
 }
 
-impl<T, const N:usize> AsRef<[T]> for Stack<T,N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> AsRef<[T]> for StackBase<T,S,L> {
     fn as_ref(&self) -> &[T] { self.as_slice() }
 }
 
-impl<T, const N:usize> AsMut<[T]> for Stack<T,N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> AsMut<[T]> for StackBase<T,S,L> {
     fn as_mut(&mut self) -> &mut [T] { self.as_mut_slice() }
 }
 
-impl<T, const N:usize> Borrow<[T]> for Stack<T,N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> Borrow<[T]> for StackBase<T,S,L> {
     fn borrow(&self) -> &[T] { self.as_slice() }
 }
 
-impl<T, const N:usize> BorrowMut<[T]> for Stack<T,N> {
+impl<T, S:?Sized+Storage<T>, L:LenType> BorrowMut<[T]> for StackBase<T,S,L> {
     fn borrow_mut(&mut self) -> &mut [T] { self.as_mut_slice() }
 }
 
-impl<T, I:SliceIndex<[T]>, const N:usize> Index<I> for Stack<T,N> {
+impl<T, I:SliceIndex<[T]>, S:?Sized+Storage<T>, L:LenType> Index<I> for StackBase<T,S,L> {
     type Output = I::Output;
     fn index(&self, i:I) -> &Self::Output {
         &self.as_slice()[i]
     }
 }
 
-impl<T, I:SliceIndex<[T]>, const N:usize> IndexMut<I> for Stack<T,N> {
+impl<T, I:SliceIndex<[T]>, S:?Sized+Storage<T>, L:LenType> IndexMut<I> for StackBase<T,S,L> {
     fn index_mut(&mut self, i:I) -> &mut Self::Output {
         &mut self.as_mut_slice()[i]
     }
 }
 
-impl<T:Eq, const N:usize> Eq for Stack<T,N> {}
+impl<T:Eq, S:?Sized+Storage<T>, L:LenType> Eq for StackBase<T,S,L> {}
 
-impl<T:PartialEq<U>, U, const N:usize, const M:usize> PartialEq<Stack<U,M>> for Stack<T,N> {
-    fn eq(&self, other: &Stack<U,M>) -> bool { self.as_slice().eq(other.as_slice()) }
-    fn ne(&self, other: &Stack<U,M>) -> bool { self.as_slice().ne(other.as_slice()) }
+impl<T:PartialEq<U>, U, S:?Sized+Storage<T>, L:LenType, S2:?Sized+Storage<U>, L2:LenType> PartialEq<StackBase<U,S2,L2>> for StackBase<T,S,L> {
+    fn eq(&self, other: &StackBase<U,S2,L2>) -> bool { self.as_slice().eq(other.as_slice()) }
+    fn ne(&self, other: &StackBase<U,S2,L2>) -> bool { self.as_slice().ne(other.as_slice()) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize, const M:usize> PartialEq<[U;M]> for Stack<T,N> {
+impl<T:PartialEq<U>, U, S:?Sized+Storage<T>, L:LenType, const M:usize> PartialEq<[U;M]> for StackBase<T,S,L> {
     fn eq(&self, other: &[U;M]) -> bool { self.as_slice().eq(other) }
     fn ne(&self, other: &[U;M]) -> bool { self.as_slice().ne(other) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize, const M:usize> PartialEq<Stack<U,M>> for [T;N] {
-    fn eq(&self, other: &Stack<U,M>) -> bool { self.eq(other.as_slice()) }
-    fn ne(&self, other: &Stack<U,M>) -> bool { self.ne(other.as_slice()) }
+impl<T:PartialEq<U>, U, const N:usize, L:LenType, S:?Sized+Storage<U>> PartialEq<StackBase<U,S,L>> for [T;N] {
+    fn eq(&self, other: &StackBase<U,S,L>) -> bool { self.eq(other.as_slice()) }
+    fn ne(&self, other: &StackBase<U,S,L>) -> bool { self.ne(other.as_slice()) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize> PartialEq<[U]> for Stack<T,N> {
+impl<T:PartialEq<U>, U, S:?Sized+Storage<T>, L:LenType> PartialEq<[U]> for StackBase<T,S,L> {
     fn eq(&self, other: &[U]) -> bool { self.as_slice().eq(other) }
     fn ne(&self, other: &[U]) -> bool { self.as_slice().ne(other) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize> PartialEq<&[U]> for Stack<T,N> {
+impl<T:PartialEq<U>, U, S:?Sized+Storage<T>, L:LenType> PartialEq<&[U]> for StackBase<T,S,L> {
     fn eq(&self, other: &&[U]) -> bool { self.as_slice().eq(*other) }
     fn ne(&self, other: &&[U]) -> bool { self.as_slice().ne(*other) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize> PartialEq<&mut [U]> for Stack<T,N> {
+impl<T:PartialEq<U>, U, S:?Sized+Storage<T>, L:LenType> PartialEq<&mut [U]> for StackBase<T,S,L> {
     fn eq(&self, other: &&mut [U]) -> bool { self.as_slice().eq(*other) }
     fn ne(&self, other: &&mut [U]) -> bool { self.as_slice().ne(*other) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize> PartialEq<Stack<U,N>> for [T] {
-    fn eq(&self, other: &Stack<U,N>) -> bool { self.eq(other.as_slice()) }
-    fn ne(&self, other: &Stack<U,N>) -> bool { self.ne(other.as_slice()) }
+impl<T:PartialEq<U>, U, S:?Sized+Storage<U>, L:LenType> PartialEq<StackBase<U,S,L>> for [T] {
+    fn eq(&self, other: &StackBase<U,S,L>) -> bool { self.eq(other.as_slice()) }
+    fn ne(&self, other: &StackBase<U,S,L>) -> bool { self.ne(other.as_slice()) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize> PartialEq<Stack<U,N>> for &[T] {
-    fn eq(&self, other: &Stack<U,N>) -> bool { (**self).eq(other) }
-    fn ne(&self, other: &Stack<U,N>) -> bool { (**self).ne(other) }
+impl<T:PartialEq<U>, U, S:?Sized+Storage<U>, L:LenType> PartialEq<StackBase<U,S,L>> for &[T] {
+    fn eq(&self, other: &StackBase<U,S,L>) -> bool { (**self).eq(other) }
+    fn ne(&self, other: &StackBase<U,S,L>) -> bool { (**self).ne(other) }
 }
 
-impl<T:PartialEq<U>, U, const N:usize> PartialEq<Stack<U,N>> for &mut[T] {
-    fn eq(&self, other: &Stack<U,N>) -> bool { (**self).eq(other) }
-    fn ne(&self, other: &Stack<U,N>) -> bool { (**self).ne(other) }
+impl<T:PartialEq<U>, U, S:?Sized+Storage<U>, L:LenType> PartialEq<StackBase<U,S,L>> for &mut[T] {
+    fn eq(&self, other: &StackBase<U,S,L>) -> bool { (**self).eq(other) }
+    fn ne(&self, other: &StackBase<U,S,L>) -> bool { (**self).ne(other) }
 }
 
-impl<T:Hash, const N:usize> Hash for Stack<T,N> {
+impl<T:PartialOrd, S:?Sized+Storage<T>, L:LenType, S2:?Sized+Storage<T>, L2:LenType> PartialOrd<StackBase<T,S2,L2>> for StackBase<T,S,L> {
+    fn partial_cmp(&self, other: &StackBase<T,S2,L2>) -> Option<core::cmp::Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T:Ord, S:?Sized+Storage<T>, L:LenType> Ord for StackBase<T,S,L> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T:Hash, S:?Sized+Storage<T>, L:LenType> Hash for StackBase<T,S,L> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.as_slice().hash(state);
     }
 }
 
-impl<T:Debug, const N:usize> Debug for Stack<T,N> {
+impl<T:Debug, S:?Sized+Storage<T>, L:LenType> Debug for StackBase<T,S,L> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         Debug::fmt(self.as_slice(), f)
     }
@@ -219,7 +548,9 @@ impl<T> Stack<T, 0> {
     }
 }
 
-impl<T, const N:usize> Stack<T, N> {
+impl<T, const N:usize, L:LenType> Stack<T,N,L> {
+
+    const LEN_FITS: () = assert!(N <= L::MAX, "The capacity of the stack doesn't fit in its length type");
 
     ///
     /// Creates an empty stack
@@ -231,12 +562,20 @@ impl<T, const N:usize> Stack<T, N> {
     /// ```
     /// # use stack_stack::Stack;
     /// let mut stack: Stack<i32, 10> = Stack::new();
+    /// 
+    /// //with a smaller length type
+    /// let mut stack: Stack<i32, 10, u8> = Stack::new();
     /// ```
     /// 
     pub const fn new() -> Self {
-        Self { len: 0, data: unsafe { MaybeUninit::uninit().assume_init() } }
+        let () = Self::LEN_FITS;
+        Self { len: L::ZERO, _marker: PhantomData, data: unsafe { MaybeUninit::uninit().assume_init() } }
     }
 
+}
+
+impl<T, const N:usize> Stack<T,N> {
+
     ///
     /// Creates a stack and fills it with the values of an array
     /// 
@@ -255,7 +594,7 @@ impl<T, const N:usize> Stack<T, N> {
     /// ```
     /// 
     pub fn from_array(array: [T;N]) -> Self {
-        Self { len:array.len(), data: array.map(|t| MaybeUninit::new(t)) }
+        Self { len:array.len(), _marker: PhantomData, data: array.map(|t| MaybeUninit::new(t)) }
     }
 
     ///
@@ -317,15 +656,35 @@ impl<T, const N:usize> Stack<T, N> {
     /// ```
     /// 
     pub const unsafe fn from_raw_parts(buf: [MaybeUninit<T>; N], len: usize) -> Self {
-        Self { len, data: buf }
+        Self { len, _marker: PhantomData, data: buf }
     }
 
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> StackBase<T,S,L> {
+
+    fn buf(&self) -> &[MaybeUninit<T>] { self.data.as_uninit() }
+
+    fn buf_mut(&mut self) -> &mut [MaybeUninit<T>] { self.data.as_uninit_mut() }
+
     /// The quantity of values in the stack
-    pub const fn len(&self) -> usize { self.len }
+    pub const fn len(&self) -> usize { len_to_usize(&self.len) }
 
+    ///
     /// The total quantity of values that this stack can hold.
-    /// Equivalent to `N`
-    pub const fn capacity(&self) -> usize { N }
+    /// Equivalent to `N` for a [`Stack`]
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::Stack;
+    /// static BUF: Stack<u8, 16> = Stack::new();
+    /// const CAPACITY: usize = BUF.capacity();
+    /// assert_eq!(CAPACITY, 16);
+    /// ```
+    /// 
+    pub const fn capacity(&self) -> usize {
+        unsafe { storage::buffer::<T,S>(&self.data).1 }
+    }
     
     /// `true` when this stack contains no elements
     pub const fn is_empty(&self) -> bool { self.len() == 0 }
@@ -334,19 +693,22 @@ impl<T, const N:usize> Stack<T, N> {
     pub const fn is_full(&self) -> bool { self.len() >= self.capacity() }
 
     /// Returns a pointer to the data in the stack
-    pub const fn as_ptr(&self) -> *const T { self.data.as_ptr() as *const _ }
+    pub const fn as_ptr(&self) -> *const T {
+        unsafe { storage::buffer::<T,S>(&self.data).0 as *const T }
+    }
 
     /// Returns a mutable pointer to the data in the stack
-    pub fn as_mut_ptr(&mut self) -> *mut T { self.data.as_mut_ptr() as *mut _ }
+    pub fn as_mut_ptr(&mut self) -> *mut T { self.buf_mut().as_mut_ptr() as *mut _ }
 
-    /// Returns a slice of the data in the stac
+    /// Returns a slice of the data in the stack
     pub const fn as_slice(&self) -> &[T] {
-        unsafe { from_raw_parts(self.data.as_ptr() as *const _, self.len) }
+        unsafe { from_raw_parts(self.as_ptr(), self.len()) }
     }
 
     /// Returns a mutable slice of the data in the stack
     pub fn as_mut_slice(&mut self) -> &mut [T] {
-        unsafe { from_raw_parts_mut(self.data.as_mut_ptr() as *mut _, self.len) }
+        let len = self.len();
+        unsafe { from_raw_parts_mut(self.as_mut_ptr(), len) }
     }
 
     ///
@@ -385,8 +747,9 @@ impl<T, const N:usize> Stack<T, N> {
     /// 
     pub fn push(&mut self, x:T) -> Result<(),T> {
         if self.is_full() { return Err(x); }
-        self.data[self.len] = MaybeUninit::new(x);
-        self.len += 1;
+        let len = self.len();
+        self.buf_mut()[len] = MaybeUninit::new(x);
+        unsafe { self.set_len(len + 1) }
         Ok(())
     }
 
@@ -407,16 +770,23 @@ impl<T, const N:usize> Stack<T, N> {
     /// assert_eq!(s.pop(), Some(6));
     /// assert_eq!(s.pop(), None);
     /// 
-    /// assert_eq!(s, []);
+    /// assert_eq!(s, [] as [i32; 0]);
     /// 
     /// ```
     /// 
     pub fn pop(&mut self) -> Option<T> {
         if self.is_empty() { return None; }
-        self.len -= 1;
-        unsafe { Some(self.data[self.len].assume_init_read()) }
+        let len = self.len() - 1;
+        unsafe {
+            self.set_len(len);
+            Some(self.buf()[len].assume_init_read())
+        }
     }
 
+}
+
+impl<T, const N:usize, L:LenType> Stack<T,N,L> {
+
     ///
     /// Copies the contents of this stack into another stack of a different
     /// capacity
@@ -444,14 +814,118 @@ impl<T, const N:usize> Stack<T, N> {
     /// 
     /// ```
     /// 
-    pub fn resize_capacity<const M: usize>(self) -> Stack<T,M> {
+    pub fn resize_capacity<const M: usize>(self) -> Stack<T,M,L> {
+        self.resize_capacity_with_overflow().0
+    }
+
+    ///
+    /// Moves the contents of this stack into another stack of a different
+    /// capacity, or returns `self` in an `Err()` if they wouldn't all fit
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s1 = stack![6, 2, 8; 10];
+    /// 
+    /// let s2 = s1.clone().try_resize_capacity::<3>().unwrap();
+    /// assert_eq!(s2, [6, 2, 8]);
+    /// assert_eq!(s2.capacity(), 3);
+    /// 
+    /// let s3 = s1.clone().try_resize_capacity::<2>();
+    /// assert_eq!(s3, Err(s1));
+    /// 
+    /// ```
+    /// 
+    pub fn try_resize_capacity<const M: usize>(self) -> Result<Stack<T,M,L>,Self> {
+        if self.len() > M { return Err(self); }
+        Ok(self.resize_capacity_with_overflow().0)
+    }
+
+    ///
+    /// Moves the contents of this stack into another stack of a different
+    /// capacity, returning an iterator over any items that didn't fit
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s1 = stack![6, 2, 8, 3, 1; 5];
+    /// 
+    /// let (s2, rest) = s1.resize_capacity_with_overflow::<3>();
+    /// assert_eq!(s2, [6, 2, 8]);
+    /// assert_eq!(s2.capacity(), 3);
+    /// assert!(rest.eq([3, 1]));
+    /// 
+    /// ```
+    /// 
+    pub fn resize_capacity_with_overflow<const M: usize>(self) -> (Stack<T,M,L>, IntoIter<T,N,L>) {
         let mut new = Stack::new();
-        for x in self {
-            if new.push(x).is_err() { break; } //stop early if M < N
+        let count = self.len().min(M);
+        unsafe {
+            let src = self.data.as_ptr();
+            let dest = new.data.as_mut_ptr();
+            copy_nonoverlapping(src, dest, count);
+            new.set_len(count);
         }
-        new
+        (new, IntoIter { index: count, stack: self })
+    }
+
+    ///
+    /// Moves the contents of this stack into another stack with at least
+    /// as much capacity
+    /// 
+    /// Unlike [`Self::resize_capacity()`], this can never lose any items,
+    /// as it fails to compile if the new capacity is smaller.
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s1 = stack![6, 2, 8; 3];
+    /// let s2 = s1.grow::<10>();
+    /// assert_eq!(s2, [6, 2, 8]);
+    /// assert_eq!(s2.capacity(), 10);
+    /// ```
+    /// 
+    /// ```compile_fail
+    /// # use stack_stack::{Stack, stack};
+    /// let s1 = stack![6, 2, 8; 3];
+    /// let s2 = s1.grow::<2>();
+    /// ```
+    /// 
+    pub fn grow<const M: usize>(self) -> Stack<T,M,L> {
+        const { assert!(M >= N, "Attempted to grow a stack to a smaller capacity") };
+        self.resize_capacity_with_overflow().0
+    }
+
+    ///
+    /// Moves the contents of this stack into a stack with a different
+    /// [`LenType`]
+    /// 
+    /// Fails to compile if the capacity doesn't fit in the new length type.
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s1 = stack![6, 2, 8; 16];
+    /// let s2: Stack<i32, 16, u8> = s1.into_len_type();
+    /// assert_eq!(s2, [6, 2, 8]);
+    /// ```
+    /// 
+    pub fn into_len_type<M:LenType>(self) -> Stack<T,N,M> {
+        let () = Stack::<T,N,M>::LEN_FITS;
+        let this = ManuallyDrop::new(self);
+        Stack { len: M::from_usize(this.len()), _marker: PhantomData, data: unsafe { core::ptr::read(&this.data) } }
     }
 
+    /// Returns this stack as a [`StackView`], erasing its capacity
+    pub fn as_view(&self) -> &StackView<T,L> { self }
+
+    /// Returns this stack as a mutable [`StackView`], erasing its capacity
+    pub fn as_view_mut(&mut self) -> &mut StackView<T,L> { self }
+
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> StackBase<T,S,L> {
+
     // pub fn into_vec() -> 
     // pub fn into_boxed_slice() 
 
@@ -493,7 +967,7 @@ impl<T, const N:usize> Stack<T, N> {
     /// examples
     /// 
     pub unsafe fn set_len(&mut self, len: usize) {
-        self.len = len;
+        self.len = L::from_usize(len);
     }
 
     fn check_bounds(&self, index: usize, op:&str) {
@@ -509,6 +983,12 @@ impl<T, const N:usize> Stack<T, N> {
         }
     }
 
+    fn check_depth(&self, depth: usize, op:&str) {
+        if depth > self.len() {
+            panic!("Attempted to {op} the top {depth} items, but the len was {}", self.len());
+        }
+    }
+
     ///
     /// Quickly removes and returns the element at `index` by swapping it with
     /// the last element in the stack
@@ -528,11 +1008,14 @@ impl<T, const N:usize> Stack<T, N> {
     pub fn swap_remove(&mut self, index: usize) -> T {
         self.check_bounds(index, "remove");
         unsafe {
-            let ret = self.data[index].assume_init_read();
-            self.len -= 1;
-            copy_nonoverlapping(
-                self.data[self.len].as_ptr(),
-                self.data[index].as_mut_ptr(),
+            let ret = self.buf()[index].assume_init_read();
+            let len = self.len() - 1;
+            self.set_len(len);
+            //the last element may be the one being removed
+            let ptr = self.buf_mut().as_mut_ptr();
+            copy(
+                ptr.add(len),
+                ptr.add(index),
                 1
             );
             ret
@@ -543,7 +1026,11 @@ impl<T, const N:usize> Stack<T, N> {
     /// Adds an element to the stack at an index
     /// 
     /// If the stack is full, then the value is still inserted, but the last
-    /// element of the stack is removed and returned.
+    /// element of the stack is removed and returned. Inserting at an index
+    /// equal to the length of the stack is the same as [`push`](Self::push()).
+    /// 
+    /// # Panics
+    /// Panics if the index is greater than the length of the stack
     /// 
     /// # Examples
     /// ```
@@ -556,83 +1043,206 @@ impl<T, const N:usize> Stack<T, N> {
     /// assert_eq!(s1.insert(2, 10), Some(5));
     /// assert_eq!(s1, [1, 2, 10, 10, 3, 4]);
     /// 
+    /// assert_eq!(s1.insert(6, 8), Some(8));
+    /// assert_eq!(s1, [1, 2, 10, 10, 3, 4]);
+    /// 
     /// ```
     /// 
     pub fn insert(&mut self, index: usize, element: T) -> Option<T> {
+        if index == self.len() { return self.push(element).err(); }
         self.check_bounds(index, "insert");
         let mut temp = MaybeUninit::new(element);
         for i in index..self.len() {
-            swap(&mut self.data[i], &mut temp);
+            swap(&mut self.buf_mut()[i], &mut temp);
         }
 
         if self.is_full() {
             //return the overflow
             Some(unsafe { temp.assume_init() })
         } else {
-            self.data[self.len] = temp;
-            self.len += 1;
+            let len = self.len();
+            self.buf_mut()[len] = temp;
+            unsafe { self.set_len(len + 1) }
             None
         }
 
     }
 
     ///
-    /// Removes and returns the element at a given index
+    /// Inserts clones of the values in a slice into the stack at an index
+    /// 
+    /// The elements after `index` are only shifted over once. If inserting
+    /// would take the stack over-capacity, then as many values as possible
+    /// are inserted and a sub-slice of the remaining elements is returned,
+    /// similarly to [`Self::extend_from_slice()`].
     /// 
     /// # Panics
-    /// Panics if the index is greater than the size of the stack
+    /// Panics if the index is greater than the length of the stack
     /// 
     /// # Examples
     /// ```
     /// # use stack_stack::{Stack, stack};
-    /// let mut s1 = stack![1, 2, 3, 4, 5; 5];
-    /// assert_eq!(s1.remove(2), 3);
-    /// assert_eq!(s1, [1, 2, 4, 5]);
+    /// let mut s1 = stack![6, 2, 8; 6];
+    /// assert_eq!(s1.insert_slice(1, &[3, 1]), Ok(()));
+    /// assert_eq!(s1, [6, 3, 1, 2, 8]);
+    /// 
+    /// assert_eq!(s1.insert_slice(5, &[0, 9]), Err(&[9] as &[_]));
+    /// assert_eq!(s1, [6, 3, 1, 2, 8, 0]);
     /// ```
     /// 
-    pub fn remove(&mut self, index: usize) -> T {
-        self.check_bounds(index, "remove");
-        unsafe {
-            let ret = self.data[index].assume_init_read();
-            for i in index+1..self.len() {
-                copy_nonoverlapping(
-                    self.data[i].as_ptr(), self.data[i-1].as_mut_ptr(), 1
-                )
-            }
-            self.len -= 1;
-            ret
-        }
+    pub fn insert_slice<'a>(&mut self, index: usize, other:&'a[T]) -> Result<(),&'a[T]>
+    where T:Clone
+    {
+        let count = (self.capacity() - self.len()).min(other.len());
+        self.insert_from_iter(index, other[..count].iter().cloned()).ok();
+        if count < other.len() { Err(&other[count..]) } else { Ok(()) }
     }
 
     ///
-    /// Removes all elements from the stack
+    /// Inserts the values from an iterator into the stack at an index
+    /// 
+    /// The elements after `index` are moved out of the way once and moved
+    /// back once. If inserting would take the stack over-capacity, then as
+    /// many values as possible are inserted and the iterator of the
+    /// remaining elements is returned, similarly to [`Self::extend_from_iter()`].
+    /// 
+    /// # Panics
+    /// Panics if the index is greater than the length of the stack
     /// 
     /// # Examples
     /// ```
     /// # use stack_stack::{Stack, stack};
-    /// let mut s1 = stack![1, 2, 3, 4, 5; 5];
-    /// s1.clear();
-    /// assert_eq!(s1, []);
+    /// let mut s1 = stack![9, 9; 10];
+    /// assert_eq!(s1.insert_from_iter(1, 0..3), Ok(()));
+    /// assert_eq!(s1, [9, 0, 1, 2, 9]);
+    /// 
+    /// let mut s2 = stack![9, 9; 5];
+    /// assert_eq!(s2.insert_from_iter(1, 0..10), Err(3..10));
+    /// assert_eq!(s2, [9, 0, 1, 2, 9]);
     /// ```
     /// 
-    pub fn clear(&mut self) {
-        if needs_drop::<T>() {
-            for i in 0..self.len {
-                unsafe { self.data[i].assume_init_drop(); }
+    pub fn insert_from_iter<I:Iterator<Item=T>>(&mut self, index: usize, mut iter:I) -> Result<(),I> {
+        let mut cursor = self.cursor_mut(index);
+        cursor.batch();
+        loop {
+            if cursor.is_full() {
+                return Err(iter);
+            } else if let Some(x) = iter.next() {
+                cursor.insert_before(x).ok();
+            } else {
+                return Ok(());
             }
         }
-        self.len = 0;
     }
 
     ///
-    /// Resizes the stack in place to the given length, filling with `x` when needed
-    /// 
-    /// If the new length is less than the current one, the stack is truncated
-    /// to the new size, else, the stack is grown to the new size, cloning `x`
-    /// in to fill the new space as needed.
+    /// Removes and returns the element at a given index
     /// 
     /// # Panics
-    /// Panics if the new length is greater than the capacity
+    /// Panics if the index is greater than the size of the stack
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s1 = stack![1, 2, 3, 4, 5; 5];
+    /// assert_eq!(s1.remove(2), 3);
+    /// assert_eq!(s1, [1, 2, 4, 5]);
+    /// ```
+    /// 
+    pub fn remove(&mut self, index: usize) -> T {
+        self.check_bounds(index, "remove");
+        unsafe {
+            let ret = self.buf()[index].assume_init_read();
+            let ptr = self.buf_mut().as_mut_ptr();
+            for i in index+1..self.len() {
+                copy_nonoverlapping(ptr.add(i), ptr.add(i-1), 1)
+            }
+            self.set_len(self.len() - 1);
+            ret
+        }
+    }
+
+    ///
+    /// Keeps only the elements for which the predicate returns `true`,
+    /// preserving their order
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s1 = stack![6, 2, 8, 3, 1; 5];
+    /// s1.retain(|x| x % 2 == 0);
+    /// assert_eq!(s1, [6, 2, 8]);
+    /// ```
+    /// 
+    pub fn retain<F:FnMut(&T)->bool>(&mut self, mut f:F) {
+        self.retain_mut(|x| f(x))
+    }
+
+    ///
+    /// Keeps only the elements for which the predicate returns `true`,
+    /// preserving their order, while allowing the predicate to modify them
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s1 = stack![6, 2, 8, 3, 1; 5];
+    /// s1.retain_mut(|x| { *x += 1; *x > 3 });
+    /// assert_eq!(s1, [7, 9, 4]);
+    /// ```
+    /// 
+    pub fn retain_mut<F:FnMut(&mut T)->bool>(&mut self, mut f:F) {
+        let len = self.len();
+
+        //if `f` or a destructor panics, the guard still closes the gap
+        unsafe { self.set_len(0) }
+        let mut guard = Retain { stack: self, len, processed: 0, deleted: 0 };
+        let ptr = guard.stack.as_mut_ptr();
+
+        while guard.processed < len {
+            let current = unsafe { &mut *ptr.add(guard.processed) };
+            let keep = f(current);
+            guard.processed += 1;
+            if !keep {
+                guard.deleted += 1;
+                unsafe { drop_in_place(current) }
+            } else if guard.deleted > 0 {
+                unsafe {
+                    let i = guard.processed - 1;
+                    copy_nonoverlapping(ptr.add(i), ptr.add(i - guard.deleted), 1);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Removes all elements from the stack
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s1 = stack![1, 2, 3, 4, 5; 5];
+    /// s1.clear();
+    /// assert_eq!(s1, [] as [i32; 0]);
+    /// ```
+    /// 
+    pub fn clear(&mut self) {
+        if needs_drop::<T>() {
+            for i in 0..self.len() {
+                unsafe { self.buf_mut()[i].assume_init_drop(); }
+            }
+        }
+        self.len = L::ZERO;
+    }
+
+    ///
+    /// Resizes the stack in place to the given length, filling with `x` when needed
+    /// 
+    /// If the new length is less than the current one, the stack is truncated
+    /// to the new size, else, the stack is grown to the new size, cloning `x`
+    /// in to fill the new space as needed.
+    /// 
+    /// # Panics
+    /// Panics if the new length is greater than the capacity
     /// 
     /// # Examples
     /// ```
@@ -745,29 +1355,442 @@ impl<T, const N:usize> Stack<T, N> {
         }
     }
 
+    ///
+    /// Pushes every element of an array onto the stack at once
+    ///
+    /// This is all-or-nothing: if the whole array doesn't fit, it is returned
+    /// in an `Err()` and the stack is left unchanged
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2; 5];
+    /// assert_eq!(s.push_array([8, 3]), Ok(()));
+    /// assert_eq!(s.push_array([1, 8]), Err([1, 8]));
+    /// assert_eq!(s, [6, 2, 8, 3]);
+    /// ```
+    ///
+    pub fn push_array<const K:usize>(&mut self, array:[T;K]) -> Result<(),[T;K]> {
+        if self.capacity() - self.len() < K { return Err(array); }
+        let array = ManuallyDrop::new(array);
+        unsafe {
+            copy_nonoverlapping(array.as_ptr(), self.as_mut_ptr().add(self.len()), K);
+            self.set_len(self.len() + K);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Appends the stack with values from an array
+    ///
+    /// If extending would take the stack over-capacity, then
+    /// as many values as possible are pushed in and an iterator
+    /// of the remaining elements is returned
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2; 5];
+    /// let rest = s.extend_from_array([8, 3, 1, 8]).unwrap_err();
+    /// assert_eq!(s, [6, 2, 8, 3, 1]);
+    /// assert_eq!(rest.as_slice(), [8]);
+    /// ```
+    ///
+    pub fn extend_from_array<const K:usize>(&mut self, array:[T;K]) -> Result<(), core::array::IntoIter<T,K>> {
+        self.extend_from_iter(array.into_iter())
+    }
+
+    ///
+    /// Removes the top `K` elements of the stack and returns them in their
+    /// original order
+    ///
+    /// If the stack has fewer than `K` elements, `None` is returned and the
+    /// stack is left unchanged
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8, 3, 1; 5];
+    /// assert_eq!(s.pop_array::<2>(), Some([3, 1]));
+    /// assert_eq!(s.pop_array::<4>(), None);
+    /// assert_eq!(s, [6, 2, 8]);
+    /// ```
+    ///
+    pub fn pop_array<const K:usize>(&mut self) -> Option<[T;K]> {
+        if self.len() < K { return None; }
+        let len = self.len() - K;
+        unsafe {
+            self.set_len(len);
+            Some((self.as_ptr().add(len) as *const [T;K]).read())
+        }
+    }
+
+    ///
+    /// Returns a reference to the top `K` elements of the stack
+    ///
+    /// If the stack has fewer than `K` elements, `None` is returned instead
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s = stack![6, 2, 8, 3, 1; 5];
+    /// assert_eq!(s.top_array::<3>(), Some(&[8, 3, 1]));
+    /// assert_eq!(s.top_array::<6>(), None);
+    /// ```
+    ///
+    pub fn top_array<const K:usize>(&self) -> Option<&[T;K]> {
+        self.len().checked_sub(K).and_then(|i| self[i..].try_into().ok())
+    }
+
+    ///
+    /// Returns a mutable reference to the top `K` elements of the stack
+    ///
+    /// If the stack has fewer than `K` elements, `None` is returned instead
+    ///
+    pub fn top_array_mut<const K:usize>(&mut self) -> Option<&mut [T;K]> {
+        self.len().checked_sub(K).and_then(move |i| (&mut self[i..]).try_into().ok())
+    }
+
+    ///
+    /// Returns a reference to the last element of the stack
+    ///
+    /// If the stack is empty, `None` is returned instead
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8; 5];
+    /// assert_eq!(s.peek(), Some(&8));
+    ///
+    /// s.clear();
+    /// assert_eq!(s.peek(), None);
+    /// ```
+    ///
+    pub fn peek(&self) -> Option<&T> { self.last() }
+
+    ///
+    /// Returns a mutable reference to the last element of the stack
+    ///
+    /// If the stack is empty, `None` is returned instead
+    ///
+    pub fn peek_mut(&mut self) -> Option<&mut T> { self.last_mut() }
+
+    ///
+    /// Returns a reference to the element `k` places below the top of the stack
+    ///
+    /// `peek_n(0)` is the top of the stack, `peek_n(1)` the element below it,
+    /// and so on. If there is no such element, `None` is returned instead
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s = stack![6, 2, 8; 5];
+    /// assert_eq!(s.peek_n(0), Some(&8));
+    /// assert_eq!(s.peek_n(2), Some(&6));
+    /// assert_eq!(s.peek_n(3), None);
+    /// assert_eq!(s.peek_n(usize::MAX), None);
+    /// ```
+    ///
+    pub fn peek_n(&self, k: usize) -> Option<&T> {
+        let len = self.len();
+        (k < len).then(|| &self[len-1-k])
+    }
+
+    ///
+    /// Returns a mutable reference to the element `k` places below the top
+    /// of the stack
+    ///
+    /// If there is no such element, `None` is returned instead
+    ///
+    pub fn peek_n_mut(&mut self, k: usize) -> Option<&mut T> {
+        let len = self.len();
+        (k < len).then(move || &mut self[len-1-k])
+    }
+
+    ///
+    /// Pushes a clone of the element `k` places below the top of the stack
+    ///
+    /// If the stack is full, the clone is returned in an `Err()` instead
+    ///
+    /// # Panics
+    /// Panics if the stack has `k` or fewer elements
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8; 5];
+    /// assert_eq!(s.pick(2), Ok(()));
+    /// assert_eq!(s, [6, 2, 8, 6]);
+    ///
+    /// assert_eq!(s.pick(0), Ok(()));
+    /// assert_eq!(s.pick(1), Err(6));
+    /// assert_eq!(s, [6, 2, 8, 6, 6]);
+    /// ```
+    ///
+    pub fn pick(&mut self, k: usize) -> Result<(),T> where T:Clone {
+        self.check_depth(k.saturating_add(1), "pick from");
+        self.push(self[self.len()-1-k].clone())
+    }
+
+    ///
+    /// Pushes a clone of the top element of the stack
+    ///
+    /// Equivalent to `pick(0)`. If the stack is full, the clone is returned
+    /// in an `Err()` instead.
+    ///
+    /// # Panics
+    /// Panics if the stack is empty
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2; 3];
+    /// assert_eq!(s.dup(), Ok(()));
+    /// assert_eq!(s, [6, 2, 2]);
+    /// assert_eq!(s.dup(), Err(2));
+    /// ```
+    ///
+    pub fn dup(&mut self) -> Result<(),T> where T:Clone { self.pick(0) }
+
+    ///
+    /// Pushes a clone of the second element from the top of the stack
+    ///
+    /// Equivalent to `pick(1)`. If the stack is full, the clone is returned
+    /// in an `Err()` instead.
+    ///
+    /// # Panics
+    /// Panics if the stack has fewer than two elements
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2; 3];
+    /// assert_eq!(s.over(), Ok(()));
+    /// assert_eq!(s, [6, 2, 6]);
+    /// ```
+    ///
+    pub fn over(&mut self) -> Result<(),T> where T:Clone { self.pick(1) }
+
+    ///
+    /// Moves the element `k` places below the top of the stack to the top,
+    /// shifting the elements above it down
+    ///
+    /// `roll(1)` is equivalent to [`Self::swap_top()`] and `roll(2)` to
+    /// [`Self::rot()`]
+    ///
+    /// # Panics
+    /// Panics if the stack has `k` or fewer elements
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8, 3, 1; 5];
+    /// s.roll(3);
+    /// assert_eq!(s, [6, 8, 3, 1, 2]);
+    /// ```
+    ///
+    pub fn roll(&mut self, k: usize) {
+        self.check_depth(k.saturating_add(1), "roll");
+        let len = self.len();
+        self[len-1-k..].rotate_left(1);
+    }
+
+    ///
+    /// Swaps the top two elements of the stack
+    ///
+    /// # Panics
+    /// Panics if the stack has fewer than two elements
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8; 5];
+    /// s.swap_top();
+    /// assert_eq!(s, [6, 8, 2]);
+    /// ```
+    ///
+    pub fn swap_top(&mut self) { self.roll(1) }
+
+    ///
+    /// Rotates the third element from the top of the stack to the top
+    ///
+    /// # Panics
+    /// Panics if the stack has fewer than three elements
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8, 3; 5];
+    /// s.rot();
+    /// assert_eq!(s, [6, 8, 3, 2]);
+    /// ```
+    ///
+    pub fn rot(&mut self) { self.roll(2) }
+
+    ///
+    /// Lazily pops elements off of the top of the stack
+    ///
+    /// Elements are only removed as the iterator is advanced, so anything
+    /// left when it is dropped stays on the stack
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![6, 2, 8, 3, 1; 5];
+    ///
+    /// let mut iter = s.pop_iter();
+    /// assert_eq!(iter.next(), Some(1));
+    /// assert_eq!(iter.next(), Some(3));
+    /// drop(iter);
+    ///
+    /// assert_eq!(s, [6, 2, 8]);
+    /// ```
+    ///
+    pub fn pop_iter(&mut self) -> PopIter<'_,T,S,L> {
+        PopIter { stack: self }
+    }
+
+    ///
+    /// Lazily pops elements off of the top of the stack for as long as they
+    /// satisfy a predicate
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// //pop operators until reaching a left parenthesis
+    /// let mut ops = stack!['(', '+', '*', '(', '-', '/'; 10];
+    ///
+    /// let out: String = ops.pop_while(|op| *op != '(').collect();
+    /// assert_eq!(out, "/-");
+    /// assert_eq!(ops, ['(', '+', '*', '(']);
+    /// ```
+    ///
+    pub fn pop_while<P:FnMut(&T)->bool>(&mut self, pred:P) -> PopWhile<'_,T,S,P,L> {
+        PopWhile { stack: self, pred }
+    }
+
 }
 
-impl<T,const N:usize> IntoIterator for Stack<T,N> {
+impl<T,const N:usize, L:LenType> IntoIterator for Stack<T,N,L> {
     type Item = T;
-    type IntoIter = IntoIter<T,N>;
+    type IntoIter = IntoIter<T,N,L>;
     fn into_iter(self) -> Self::IntoIter {
         IntoIter { index: 0, stack: self }
     }
 }
 
+impl<'a, T, S:?Sized+Storage<T>, L:LenType> IntoIterator for &'a StackBase<T,S,L> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a,T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, S:?Sized+Storage<T>, L:LenType> IntoIterator for &'a mut StackBase<T,S,L> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a,T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+///
+/// Pushes every value of the iterator onto the stack
+/// 
+/// # Panics
+/// Panics if the iterator has more values than the remaining capacity. Use
+/// [`Stack::extend_from_iter()`] to handle overflow instead.
+/// 
+impl<T, S:?Sized+Storage<T>, L:LenType> Extend<T> for StackBase<T,S,L> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        for x in iter {
+            if self.push(x).is_err() {
+                panic!("Attempted to extend a stack past its capacity of {}", self.capacity());
+            }
+        }
+    }
+}
+
+///
+/// Pushes a copy of every value of the iterator onto the stack
+/// 
+/// # Panics
+/// Panics if the iterator has more values than the remaining capacity. Use
+/// [`Stack::extend_from_slice()`] to handle overflow instead.
+/// 
+impl<'a, T:Copy+'a, S:?Sized+Storage<T>, L:LenType> Extend<&'a T> for StackBase<T,S,L> {
+    fn extend<I:IntoIterator<Item=&'a T>>(&mut self, iter:I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
 /// An iterator over the values of a [`Stack`]
-pub struct IntoIter<T, const N:usize> {
+pub struct IntoIter<T, const N:usize, L:LenType = usize> {
     index: usize,
-    stack: Stack<T,N>
+    stack: Stack<T,N,L>
 }
 
-impl<T, const N:usize> IntoIter<T,N> {
+impl<T, const N:usize, L:LenType> IntoIter<T,N,L> {
     fn remaining(&self) -> usize {
         self.stack.len()-self.index
     }
+
+    ///
+    /// Returns a slice of the values that have not been yielded yet
+    /// 
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut iter = stack![6, 2, 8, 3; 5].into_iter();
+    /// assert_eq!(iter.as_slice(), [6, 2, 8, 3]);
+    /// 
+    /// iter.next();
+    /// iter.next_back();
+    /// assert_eq!(iter.as_slice(), [2, 8]);
+    /// ```
+    /// 
+    pub fn as_slice(&self) -> &[T] {
+        &self.stack[self.index..]
+    }
+
+    /// Returns a mutable slice of the values that have not been yielded yet
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        let index = self.index;
+        &mut self.stack[index..]
+    }
 }
 
-impl<T, const N:usize> Iterator for IntoIter<T,N> {
+impl<T:Clone, const N:usize, L:LenType> Clone for IntoIter<T,N,L> {
+    fn clone(&self) -> Self {
+        let mut stack = Stack::new();
+        stack.extend_from_slice(self.as_slice()).ok();
+        IntoIter { index: 0, stack }
+    }
+}
+
+impl<T:Debug, const N:usize, L:LenType> Debug for IntoIter<T,N,L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T, const N:usize, L:LenType> Drop for IntoIter<T,N,L> {
+    fn drop(&mut self) {
+        //only drop the values that haven't been read out yet
+        let index = self.index;
+        unsafe {
+            let len = self.stack.len();
+            self.stack.set_len(0);
+            let remaining = &mut self.stack.data[index..len];
+            for x in remaining {
+                x.assume_init_drop();
+            }
+        }
+    }
+}
+
+impl<T, const N:usize, L:LenType> Iterator for IntoIter<T,N,L> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         if self.index >= self.stack.len() { return None; }
@@ -787,12 +1810,73 @@ impl<T, const N:usize> Iterator for IntoIter<T,N> {
 
 }
 
-impl<T, const N:usize> DoubleEndedIterator for IntoIter<T,N> {
+impl<T, const N:usize, L:LenType> DoubleEndedIterator for IntoIter<T,N,L> {
     fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining() == 0 { return None; }
         self.stack.pop()
     }
 }
 
-impl<T, const N:usize> ExactSizeIterator for IntoIter<T,N> {}
-// unsafe impl<T, const N:usize> TrustedLen for IntoIter<T,N> {}
+impl<T, const N:usize, L:LenType> ExactSizeIterator for IntoIter<T,N,L> {}
+impl<T, const N:usize, L:LenType> FusedIterator for IntoIter<T,N,L> {}
+#[cfg(feature = "nightly")]
+unsafe impl<T, const N:usize, L:LenType> TrustedLen for IntoIter<T,N,L> {}
+
+/// A lazy iterator popping values off of a [`Stack`]. See [`Stack::pop_iter()`]
+pub struct PopIter<'a, T, S:?Sized+Storage<T>, L:LenType = usize> {
+    stack: &'a mut StackBase<T,S,L>
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> Iterator for PopIter<'_,T,S,L> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> { self.stack.pop() }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.stack.len(), Some(self.stack.len()))
+    }
+}
 
+impl<T, S:?Sized+Storage<T>, L:LenType> ExactSizeIterator for PopIter<'_,T,S,L> {}
+impl<T, S:?Sized+Storage<T>, L:LenType> FusedIterator for PopIter<'_,T,S,L> {}
+
+/// A lazy iterator popping values off of a [`Stack`] while they satisfy a
+/// predicate. See [`Stack::pop_while()`]
+pub struct PopWhile<'a, T, S:?Sized+Storage<T>, P, L:LenType = usize> {
+    stack: &'a mut StackBase<T,S,L>,
+    pred: P
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType, P:FnMut(&T)->bool> Iterator for PopWhile<'_,T,S,P,L> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if (self.pred)(self.stack.peek()?) { self.stack.pop() } else { None }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.stack.len()))
+    }
+}
+
+/// Shifts the unprocessed elements back and fixes the length after a `retain`
+struct Retain<'a, T, S:?Sized+Storage<T>, L:LenType> {
+    stack: &'a mut StackBase<T,S,L>,
+    len: usize,
+    processed: usize,
+    deleted: usize
+}
+
+impl<T, S:?Sized+Storage<T>, L:LenType> Drop for Retain<'_,T,S,L> {
+    fn drop(&mut self) {
+        unsafe {
+            let ptr = self.stack.as_mut_ptr();
+            if self.deleted > 0 {
+                copy(
+                    ptr.add(self.processed),
+                    ptr.add(self.processed - self.deleted),
+                    self.len - self.processed
+                );
+            }
+            self.stack.set_len(self.len - self.deleted);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use core::mem::take;
+use core::ptr::{drop_in_place, read};
+use core::slice::{from_raw_parts_mut, IterMut};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
+use rayon::prelude::*;
+
+use crate::{LenType, Stack, StackBase, Storage};
+
+///
+/// A parallel iterator that moves the values out of a [`Stack`], with the
+/// `rayon` feature
+///
+/// Created by [`into_par_iter()`](IntoParallelIterator::into_par_iter).
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{Stack, stack};
+/// use rayon::prelude::*;
+///
+/// let s = stack![6, 2, 8, 3, 1; 8];
+/// let total: i32 = s.into_par_iter().map(|x| x * 10).sum();
+/// assert_eq!(total, 200);
+/// ```
+///
+#[derive(Debug)]
+pub struct IntoParIter<T, const N:usize, L:LenType = usize> {
+    stack: Stack<T,N,L>
+}
+
+impl<T:Send, const N:usize, L:LenType> IntoParallelIterator for Stack<T,N,L> {
+    type Item = T;
+    type Iter = IntoParIter<T,N,L>;
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter { stack: self }
+    }
+}
+
+impl<'a, T:Sync+'a, S:?Sized+Storage<T>, L:LenType> IntoParallelIterator for &'a StackBase<T,S,L> {
+    type Item = &'a T;
+    type Iter = rayon::slice::Iter<'a,T>;
+    fn into_par_iter(self) -> Self::Iter {
+        self.as_slice().into_par_iter()
+    }
+}
+
+impl<'a, T:Send+'a, S:?Sized+Storage<T>, L:LenType> IntoParallelIterator for &'a mut StackBase<T,S,L> {
+    type Item = &'a mut T;
+    type Iter = rayon::slice::IterMut<'a,T>;
+    fn into_par_iter(self) -> Self::Iter {
+        self.as_mut_slice().into_par_iter()
+    }
+}
+
+impl<T:Send, const N:usize, L:LenType> ParallelIterator for IntoParIter<T,N,L> {
+    type Item = T;
+
+    fn drive_unindexed<C:UnindexedConsumer<T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.stack.len())
+    }
+}
+
+impl<T:Send, const N:usize, L:LenType> IndexedParallelIterator for IntoParIter<T,N,L> {
+    fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn drive<C:Consumer<T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB:ProducerCallback<T>>(mut self, callback: CB) -> CB::Output {
+        //the producer takes ownership of the values, so the stack must forget them
+        let len = self.stack.len();
+        unsafe {
+            self.stack.set_len(0);
+            let values = from_raw_parts_mut(self.stack.as_mut_ptr(), len);
+            callback.callback(DrainProducer { values })
+        }
+    }
+}
+
+/// Moves values out of a slice, dropping any that are left over
+struct DrainProducer<'a, T> {
+    values: &'a mut [T]
+}
+
+impl<'a, T:Send> Producer for DrainProducer<'a, T> {
+    type Item = T;
+    type IntoIter = SliceDrain<'a, T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        //take the slice so that it isn't dropped here as well
+        SliceDrain { iter: take(&mut self.values).iter_mut() }
+    }
+
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let (left, right) = take(&mut self.values).split_at_mut(index);
+        (DrainProducer { values: left }, DrainProducer { values: right })
+    }
+}
+
+impl<T> Drop for DrainProducer<'_, T> {
+    fn drop(&mut self) {
+        let values: *mut [T] = take::<&mut [T]>(&mut self.values);
+        unsafe { drop_in_place(values) }
+    }
+}
+
+/// The sequential iterator of a [`DrainProducer`]
+struct SliceDrain<'a, T> {
+    iter: IterMut<'a, T>
+}
+
+impl<T> Iterator for SliceDrain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let ptr: *const T = self.iter.next()?;
+        Some(unsafe { read(ptr) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for SliceDrain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        let ptr: *const T = self.iter.next_back()?;
+        Some(unsafe { read(ptr) })
+    }
+}
+
+impl<T> ExactSizeIterator for SliceDrain<'_, T> {}
+
+impl<T> Drop for SliceDrain<'_, T> {
+    fn drop(&mut self) {
+        let rest = take(&mut self.iter).into_slice();
+        unsafe { drop_in_place(rest as *const [T] as *mut [T]) }
+    }
+}
+
+///
+/// Collects the values of a parallel iterator into a stack, in order
+///
+/// # Panics
+/// Panics if the iterator has more values than the capacity, the same as
+/// [`Extend`]. Check [`opt_len()`](ParallelIterator::opt_len) or limit the
+/// iterator with [`take()`](IndexedParallelIterator::take) first to avoid
+/// this. When the length is known up front, this panics before any values
+/// are produced, and otherwise as soon as one value too many is.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::Stack;
+/// use rayon::prelude::*;
+///
+/// let squares: Stack<u32, 16> = (0..10u32).into_par_iter().map(|x| x * x).collect();
+/// assert_eq!(squares, [0, 1, 4, 9, 16, 25, 36, 49, 64, 81]);
+/// ```
+///
+/// ```should_panic
+/// # use stack_stack::Stack;
+/// use rayon::prelude::*;
+///
+/// let s: Stack<u32, 4> = (0..10u32).into_par_iter().collect();
+/// ```
+///
+/// ```should_panic
+/// # use stack_stack::Stack;
+/// use rayon::prelude::*;
+///
+/// //endless, so the values can't all be gathered before checking
+/// let s: Stack<u32, 4> = rayon::iter::repeat(1).collect();
+/// ```
+///
+impl<T:Send, const N:usize, L:LenType> FromParallelIterator<T> for Stack<T,N,L> {
+    fn from_par_iter<I:IntoParallelIterator<Item=T>>(par_iter: I) -> Self {
+        let par_iter = par_iter.into_par_iter();
+
+        //gather the values in order on the heap so that the workers don't
+        //need to move whole stacks around
+        let chunks = match par_iter.opt_len() {
+            Some(len) if len > N => capacity_exceeded(N),
+            Some(_) => par_iter.collect_vec_list(),
+
+            //count the values as they are produced so that the workers stop
+            //at the first one past the capacity instead of buffering them all
+            None => {
+                let count = AtomicUsize::new(0);
+                par_iter.inspect(|_| {
+                    if count.fetch_add(1, Ordering::Relaxed) >= N {
+                        capacity_exceeded(N)
+                    }
+                }).collect_vec_list()
+            }
+        };
+
+        let mut stack = Self::new();
+        for chunk in chunks {
+            stack.extend(chunk);
+        }
+        stack
+    }
+}
+
+fn capacity_exceeded(capacity: usize) -> ! {
+    panic!("Attempted to collect into a stack past its capacity of {capacity}")
+}
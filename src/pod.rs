@@ -0,0 +1,147 @@
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::mem::size_of;
+use core::ptr::copy_nonoverlapping;
+
+use bytemuck::{Pod, PodCastError};
+
+use crate::{LenType, Stack, StackBase, Storage};
+
+///
+/// Error returned by [`Stack::from_bytes()`] when the bytes can't be read as
+/// a stack
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FromBytesError {
+    /// The bytes weren't aligned for the item type
+    Misaligned,
+    /// The quantity of bytes wasn't a multiple of the size of the item type
+    InvalidLength,
+    /// There were more items than the capacity of the stack
+    Overflow
+}
+
+impl Display for FromBytesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Misaligned => f.write_str("bytes are not aligned for the item type"),
+            Self::InvalidLength => f.write_str("byte length is not a multiple of the item size"),
+            Self::Overflow => f.write_str("too many items for the capacity of the stack"),
+        }
+    }
+}
+
+impl<T:Pod, S:?Sized+Storage<T>, L:LenType> StackBase<T,S,L> {
+
+    ///
+    /// Returns the initialized values of the stack as bytes
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s = stack![0x0201u16, 0x0403; 8];
+    /// assert_eq!(s.as_bytes(), &[1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    ///
+    /// Returns the initialized values of the stack as mutable bytes
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let mut s = stack![0u16, 0; 8];
+    /// s.as_bytes_mut().copy_from_slice(&[1, 2, 3, 4]);
+    /// assert_eq!(s, [0x0201, 0x0403]);
+    /// ```
+    ///
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+}
+
+impl<T:Pod, const N:usize, L:LenType> Stack<T,N,L> {
+
+    ///
+    /// Creates a stack by copying values out of bytes, such as those
+    /// returned by [`StackBase::as_bytes()`]
+    ///
+    /// # Errors
+    /// Returns [`FromBytesError::Misaligned`] if the bytes aren't aligned
+    /// for `T`, [`FromBytesError::InvalidLength`] if their length isn't a
+    /// multiple of the size of `T`, or [`FromBytesError::Overflow`] if they
+    /// hold more than `N` values.
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack, FromBytesError};
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// #[repr(C)]
+    /// struct Sample { time: u32, value: f32 }
+    ///
+    /// unsafe impl bytemuck::Zeroable for Sample {}
+    /// unsafe impl bytemuck::Pod for Sample {}
+    ///
+    /// let mut log: Stack<Sample, 1024> = Stack::new();
+    /// log.push(Sample { time: 0, value: 1.5 }).ok();
+    /// log.push(Sample { time: 10, value: 2.5 }).ok();
+    ///
+    /// let restored: Stack<Sample, 1024> = Stack::from_bytes(log.as_bytes()).unwrap();
+    /// assert_eq!(restored, log);
+    ///
+    /// let bytes = log.as_bytes();
+    /// assert_eq!(Stack::<Sample, 1024>::from_bytes(&bytes[..12]), Err(FromBytesError::InvalidLength));
+    /// assert_eq!(Stack::<Sample, 1>::from_bytes(bytes), Err(FromBytesError::Overflow));
+    /// assert_eq!(Stack::<u32, 4>::from_bytes(&bytes[1..5]), Err(FromBytesError::Misaligned));
+    /// ```
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self,FromBytesError> {
+        let values: &[T] = bytemuck::try_cast_slice(bytes).map_err(|e| match e {
+            PodCastError::TargetAlignmentGreaterAndInputNotAligned => FromBytesError::Misaligned,
+            _ => FromBytesError::InvalidLength
+        })?;
+
+        let mut stack = Self::new();
+        stack.extend_from_slice(values).map_err(|_| FromBytesError::Overflow)?;
+        Ok(stack)
+    }
+
+    ///
+    /// Reinterprets the values of the stack as another type of the same size
+    ///
+    /// The bytes of each value are copied as they are, so `U` doesn't need to
+    /// have the same alignment as `T`. A size mismatch is caught at compile
+    /// time.
+    ///
+    /// # Safety
+    /// The bytes of every value in the stack must be a valid `U`. This is
+    /// always the case when `U` is also [`Pod`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::{Stack, stack};
+    /// let s = stack![1.0f32, -2.0; 4];
+    /// let bits: Stack<u32, 4> = unsafe { s.cast() };
+    /// assert_eq!(bits, [0x3f80_0000, 0xc000_0000]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use stack_stack::{Stack, stack};
+    /// let s = stack![1u32, 2; 4];
+    /// let s: Stack<u16, 4> = unsafe { s.cast() };
+    /// ```
+    ///
+    pub unsafe fn cast<U:Copy>(self) -> Stack<U,N,L> {
+        const { assert!(size_of::<T>() == size_of::<U>(), "Attempted to cast between types of different sizes") };
+        let mut stack = Stack::new();
+        copy_nonoverlapping(
+            self.as_ptr() as *const u8, stack.as_mut_ptr() as *mut u8, self.len() * size_of::<T>()
+        );
+        stack.set_len(self.len());
+        stack
+    }
+
+}
@@ -0,0 +1,381 @@
+//!
+//! A wait-free, fixed-capacity, single-producer single-consumer queue
+//!
+//! Like [`Stack`](crate::Stack), the values are stored inline in a
+//! `[MaybeUninit<T>; N]` and there is no dynamic allocation whatsoever.
+//! A [`Queue`] is split into a [`Producer`] and a [`Consumer`] that can be
+//! sent to different threads (or an interrupt handler and the main loop).
+//!
+//! # Examples
+//! ```
+//! use stack_stack::spsc::Queue;
+//!
+//! let mut queue = Queue::<i32, 4>::new();
+//! let (mut tx, mut rx) = queue.split();
+//!
+//! std::thread::scope(|s| {
+//!     s.spawn(move || {
+//!         for i in 0..100 {
+//!             while tx.push(i).is_err() {}
+//!         }
+//!     });
+//!
+//!     let mut received = 0;
+//!     while received < 100 {
+//!         if let Some(x) = rx.pop() {
+//!             assert_eq!(x, received);
+//!             received += 1;
+//!         }
+//!     }
+//! });
+//! ```
+//!
+
+use core::cell::UnsafeCell;
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+///
+/// A bounded single-producer single-consumer ring buffer
+///
+/// Both indices are kept in the range `0..2*N` so that a full queue can be
+/// told apart from an empty one without sacrificing a slot.
+///
+/// Since [`Self::new()`] is `const`, a queue can be placed directly in a
+/// `static` and shared using [`Self::split_unchecked()`].
+///
+pub struct Queue<T, const N:usize> {
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    data: UnsafeCell<[MaybeUninit<T>; N]>
+}
+
+unsafe impl<T:Send, const N:usize> Sync for Queue<T,N> {}
+
+impl<T, const N:usize> Drop for Queue<T,N> {
+    fn drop(&mut self) {
+        while self.consumer().pop().is_some() {}
+    }
+}
+
+impl<T, const N:usize> Default for Queue<T,N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T, const N:usize> Debug for Queue<T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Queue")
+            .field("len", &self.len())
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+impl<T, const N:usize> Queue<T,N> {
+
+    ///
+    /// Creates an empty queue
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::spsc::Queue;
+    /// static QUEUE: Queue<u8, 16> = Queue::new();
+    /// assert!(QUEUE.is_empty());
+    /// ```
+    ///
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            data: UnsafeCell::new(unsafe { MaybeUninit::uninit().assume_init() })
+        }
+    }
+
+    ///
+    /// The quantity of values currently in the queue
+    ///
+    /// While the other half is in use, this is only a snapshot that may
+    /// already be out of date, since the head and tail are read separately.
+    /// It never exceeds the capacity.
+    ///
+    pub fn len(&self) -> usize {
+        //the tail may have moved on since the head was read, so this can
+        //briefly overshoot
+        distance(
+            self.head.load(Ordering::Acquire),
+            self.tail.load(Ordering::Acquire),
+            N
+        ).min(N)
+    }
+
+    /// The total quantity of values that this queue can hold.
+    /// Equivalent to `N`
+    pub const fn capacity(&self) -> usize { N }
+
+    /// `true` when this queue contains no elements
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// `true` when this queue contains as many elements as the capacity
+    pub fn is_full(&self) -> bool { self.len() >= N }
+
+    ///
+    /// Splits the queue into its producing and consuming halves
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::spsc::Queue;
+    /// let mut q = Queue::<i32, 3>::new();
+    /// let (mut tx, mut rx) = q.split();
+    ///
+    /// assert_eq!(tx.push(6), Ok(()));
+    /// assert_eq!(tx.push(2), Ok(()));
+    /// assert_eq!(tx.push(8), Ok(()));
+    /// assert_eq!(tx.push(3), Err(3));
+    ///
+    /// assert_eq!(rx.pop(), Some(6));
+    /// assert_eq!(rx.pop(), Some(2));
+    /// assert_eq!(rx.pop(), Some(8));
+    /// assert_eq!(rx.pop(), None);
+    /// ```
+    ///
+    pub fn split(&mut self) -> (Producer<'_,T,N>, Consumer<'_,T,N>) {
+        (self.producer(), self.consumer())
+    }
+
+    ///
+    /// Same as [`Self::split()`] but only requires a shared reference, so
+    /// that it can be used on a queue stored in a `static`
+    ///
+    /// # Safety
+    /// Caller must guarrantee that at no point are there more than one
+    /// [`Producer`] and one [`Consumer`] alive for this queue
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::spsc::Queue;
+    /// static QUEUE: Queue<u32, 8> = Queue::new();
+    ///
+    /// let (mut tx, mut rx) = unsafe { QUEUE.split_unchecked() };
+    /// tx.push(6).ok();
+    /// assert_eq!(rx.pop(), Some(6));
+    /// ```
+    ///
+    pub unsafe fn split_unchecked(&self) -> (Producer<'_,T,N>, Consumer<'_,T,N>) {
+        (self.producer(), self.consumer())
+    }
+
+    fn producer(&self) -> Producer<'_,T,N> {
+        Producer { queue: self }
+    }
+
+    fn consumer(&self) -> Consumer<'_,T,N> {
+        Consumer { queue: self }
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        let i = if index >= N { index - N } else { index };
+        unsafe { (self.data.get() as *mut MaybeUninit<T>).add(i) }
+    }
+
+}
+
+/// The number of slots from `head` to `tail` when both are kept in `0..2*n`
+fn distance(head: usize, tail: usize, n: usize) -> usize {
+    if tail >= head { tail - head } else { tail + 2*n - head }
+}
+
+/// Moves `index` forward by `count` while keeping it in `0..2*n`
+fn advance(index: usize, count: usize, n: usize) -> usize {
+    let next = index + count;
+    if next >= 2*n { next - 2*n } else { next }
+}
+
+///
+/// The pushing half of a [`Queue`]
+///
+/// Created using [`Queue::split()`]
+///
+pub struct Producer<'a, T, const N:usize> {
+    queue: &'a Queue<T,N>
+}
+
+impl<T, const N:usize> Debug for Producer<'_,T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Producer").field("queue", self.queue).finish()
+    }
+}
+
+impl<T, const N:usize> Producer<'_,T,N> {
+
+    /// The quantity of values currently in the queue
+    pub fn len(&self) -> usize { self.queue.len() }
+
+    /// The total quantity of values that the queue can hold.
+    pub const fn capacity(&self) -> usize { N }
+
+    /// `true` when the queue contains no elements
+    pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    /// `true` when the queue cannot accept any more values
+    pub fn is_full(&self) -> bool { self.queue.is_full() }
+
+    fn reserve(&self) -> (usize, usize) {
+        let tail = self.queue.tail.load(Ordering::Relaxed);
+        let head = self.queue.head.load(Ordering::Acquire);
+        (tail, N - distance(head, tail, N))
+    }
+
+    ///
+    /// Adds an element to the back of the queue
+    ///
+    /// If the queue is full, the value is returned in an `Err()`
+    ///
+    pub fn push(&mut self, x:T) -> Result<(),T> {
+        let (tail, free) = self.reserve();
+        if free == 0 { return Err(x); }
+        unsafe { (*self.queue.slot(tail)).write(x); }
+        self.queue.tail.store(advance(tail, 1, N), Ordering::Release);
+        Ok(())
+    }
+
+    ///
+    /// Adds as many values from a slice as will fit to the back of the queue
+    ///
+    /// The values are published to the consumer all at once. If the queue
+    /// does not have room for everything, a sub-slice of the remaining
+    /// elements is returned, similarly to [`Stack::extend_from_slice()`](crate::Stack::extend_from_slice)
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::spsc::Queue;
+    /// let mut q = Queue::<i32, 4>::new();
+    /// let (mut tx, mut rx) = q.split();
+    ///
+    /// assert_eq!(tx.push_slice(&[6, 2]), Ok(()));
+    /// assert_eq!(tx.push_slice(&[8, 3, 1]), Err(&[1] as &[_]));
+    ///
+    /// let mut buf = [0; 8];
+    /// assert_eq!(rx.pop_into(&mut buf), 4);
+    /// assert_eq!(buf[..4], [6, 2, 8, 3]);
+    /// ```
+    ///
+    pub fn push_slice<'b>(&mut self, other: &'b [T]) -> Result<(), &'b [T]> where T:Clone {
+        let (tail, free) = self.reserve();
+        let count = free.min(other.len());
+        for (i, x) in other[..count].iter().enumerate() {
+            unsafe { (*self.queue.slot(advance(tail, i, N))).write(x.clone()); }
+        }
+        self.queue.tail.store(advance(tail, count, N), Ordering::Release);
+        if count < other.len() { Err(&other[count..]) } else { Ok(()) }
+    }
+
+}
+
+///
+/// The popping half of a [`Queue`]
+///
+/// Created using [`Queue::split()`]
+///
+pub struct Consumer<'a, T, const N:usize> {
+    queue: &'a Queue<T,N>
+}
+
+impl<T, const N:usize> Debug for Consumer<'_,T,N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Consumer").field("queue", self.queue).finish()
+    }
+}
+
+impl<T, const N:usize> Consumer<'_,T,N> {
+
+    /// The quantity of values currently in the queue
+    pub fn len(&self) -> usize { self.queue.len() }
+
+    /// The total quantity of values that the queue can hold.
+    pub const fn capacity(&self) -> usize { N }
+
+    /// `true` when the queue contains no elements
+    pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    /// `true` when the queue contains as many elements as the capacity
+    pub fn is_full(&self) -> bool { self.queue.is_full() }
+
+    fn available(&self) -> (usize, usize) {
+        let head = self.queue.head.load(Ordering::Relaxed);
+        let tail = self.queue.tail.load(Ordering::Acquire);
+        (head, distance(head, tail, N))
+    }
+
+    ///
+    /// Removes the element at the front of the queue and returns it
+    ///
+    /// If the queue is empty, `None` is returned instead
+    ///
+    pub fn pop(&mut self) -> Option<T> {
+        let (head, len) = self.available();
+        if len == 0 { return None; }
+        let x = unsafe { (*self.queue.slot(head)).assume_init_read() };
+        self.queue.head.store(advance(head, 1, N), Ordering::Release);
+        Some(x)
+    }
+
+    ///
+    /// Returns a reference to the element at the front of the queue without
+    /// removing it
+    ///
+    /// This takes `&mut self` so that a shared `&Consumer` can't be used to
+    /// reach the values from more than one thread at once.
+    ///
+    /// # Examples
+    /// ```
+    /// # use stack_stack::spsc::Queue;
+    /// let mut q = Queue::<i32, 4>::new();
+    /// let (mut tx, mut rx) = q.split();
+    ///
+    /// assert_eq!(rx.peek(), None);
+    /// tx.push(6).ok();
+    /// assert_eq!(rx.peek(), Some(&6));
+    /// assert_eq!(rx.pop(), Some(6));
+    /// ```
+    ///
+    pub fn peek(&mut self) -> Option<&T> {
+        let (head, len) = self.available();
+        if len == 0 { return None; }
+        unsafe { Some((*self.queue.slot(head)).assume_init_ref()) }
+    }
+
+    ///
+    /// Moves as many values as possible from the front of the queue into
+    /// a slice, returning how many were moved
+    ///
+    /// The slots are released to the producer all at once. Values
+    /// previously in `buf` are dropped as they are overwritten.
+    ///
+    pub fn pop_into(&mut self, buf: &mut [T]) -> usize {
+        let (head, len) = self.available();
+        let count = len.min(buf.len());
+
+        //publish however far we got, even if dropping an old value panics
+        let mut release = Release::<N> { head: &self.queue.head, start: head, count: 0 };
+        for dest in &mut buf[..count] {
+            let x = unsafe { (*self.queue.slot(advance(head, release.count, N))).assume_init_read() };
+            release.count += 1;
+            *dest = x;
+        }
+        count
+    }
+
+}
+
+struct Release<'a, const N:usize> {
+    head: &'a AtomicUsize,
+    start: usize,
+    count: usize
+}
+
+impl<const N:usize> Drop for Release<'_,N> {
+    fn drop(&mut self) {
+        self.head.store(advance(self.start, self.count, N), Ordering::Release);
+    }
+}
@@ -0,0 +1,214 @@
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+use crate::{LenType, StackBase};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+///
+/// Where a [`Storage`] keeps its buffer, so that it can be found in `const`
+/// functions, which can't call trait methods
+///
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferKind {
+    /// The storage is an array of this length
+    Array(usize),
+    /// The storage is an unsized slice
+    Slice,
+    /// The storage is a reference to a slice
+    Borrowed
+}
+
+///
+/// The buffer that a [`StackBase`] stores its values in
+///
+/// This is implemented for owned arrays, which make up a [`Stack`](crate::Stack),
+/// for unsized slices, and for borrowed slices, which make up a [`SliceStack`].
+///
+/// This trait is sealed and cannot be implemented outside of this crate, so
+/// that stacks can find their buffer in `const` functions.
+///
+pub trait Storage<T>: sealed::Sealed {
+    #[doc(hidden)]
+    const KIND: BufferKind;
+
+    /// Returns the buffer as a slice of possibly uninitialized values
+    fn as_uninit(&self) -> &[MaybeUninit<T>];
+
+    /// Returns the buffer as a mutable slice of possibly uninitialized values
+    fn as_uninit_mut(&mut self) -> &mut [MaybeUninit<T>];
+}
+
+impl<T, const N:usize> sealed::Sealed for [MaybeUninit<T>; N] {}
+
+impl<T, const N:usize> Storage<T> for [MaybeUninit<T>; N] {
+    const KIND: BufferKind = BufferKind::Array(N);
+    fn as_uninit(&self) -> &[MaybeUninit<T>] { self }
+    fn as_uninit_mut(&mut self) -> &mut [MaybeUninit<T>] { self }
+}
+
+impl<T> sealed::Sealed for [MaybeUninit<T>] {}
+
+impl<T> Storage<T> for [MaybeUninit<T>] {
+    const KIND: BufferKind = BufferKind::Slice;
+    fn as_uninit(&self) -> &[MaybeUninit<T>] { self }
+    fn as_uninit_mut(&mut self) -> &mut [MaybeUninit<T>] { self }
+}
+
+impl<T> sealed::Sealed for &mut [MaybeUninit<T>] {}
+
+impl<T> Storage<T> for &mut [MaybeUninit<T>] {
+    const KIND: BufferKind = BufferKind::Borrowed;
+    fn as_uninit(&self) -> &[MaybeUninit<T>] { self }
+    fn as_uninit_mut(&mut self) -> &mut [MaybeUninit<T>] { self }
+}
+
+//reinterprets a pointer to a storage as the pointer its `BufferKind` describes
+union StoragePtr<T, S:?Sized> {
+    storage: *const S,
+    slice: *const [MaybeUninit<T>],
+    borrowed: *const *const [MaybeUninit<T>]
+}
+
+///
+/// Returns the start and length of the buffer of a storage, in a way that
+/// works in `const` functions
+///
+/// # Safety
+/// `storage` must point to a valid `S`
+///
+pub(crate) const unsafe fn buffer<T, S:?Sized+Storage<T>>(storage: *const S) -> (*const MaybeUninit<T>, usize) {
+    match S::KIND {
+        BufferKind::Array(n) => (storage as *const MaybeUninit<T>, n),
+        BufferKind::Slice => {
+            //the pointer has the metadata of a slice, so the union fields line up
+            let slice = StoragePtr::<T,S> { storage }.slice;
+            (slice as *const MaybeUninit<T>, slice.len())
+        },
+        BufferKind::Borrowed => {
+            //`&mut [T]` has the same layout as `*const [T]`
+            let slice = *StoragePtr::<T,S> { storage }.borrowed;
+            (slice as *const MaybeUninit<T>, slice.len())
+        }
+    }
+}
+
+///
+/// A stack that stores its values in a borrowed buffer
+///
+/// The capacity is the length of the buffer, so it is set at runtime. This
+/// is useful when the memory comes from somewhere else, such as a linker
+/// section, a `static` region or a DMA buffer. Every method of [`StackBase`]
+/// that doesn't need to create a new stack is available.
+///
+/// Values left in the stack are dropped along with it, but the buffer
+/// itself is only borrowed.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::SliceStack;
+/// use core::mem::MaybeUninit;
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let mut s = SliceStack::new(&mut buf[..3]);
+/// assert_eq!(s.capacity(), 3);
+///
+/// assert_eq!(s.extend_from_slice(&[6, 2, 8, 3]), Err(&[3] as &[_]));
+/// s.insert(1, 1);
+/// assert_eq!(s, [6, 1, 2]);
+/// ```
+///
+pub type SliceStack<'a, T> = StackBase<T, &'a mut [MaybeUninit<T>]>;
+
+impl<'a, T> SliceStack<'a, T> {
+
+    ///
+    /// Creates an empty stack using a buffer
+    ///
+    pub fn new(buf: &'a mut [MaybeUninit<T>]) -> Self {
+        unsafe { Self::from_raw_parts(buf, 0) }
+    }
+
+    ///
+    /// Creates a stack using a buffer whose first `len` values are already
+    /// initialized
+    ///
+    /// # Safety
+    /// Caller must guarrantee that the first `len` values in `buf` are properly
+    /// initialized and that `len` is less than or equal to the length of `buf`
+    ///
+    pub unsafe fn from_raw_parts(buf: &'a mut [MaybeUninit<T>], len: usize) -> Self {
+        StackBase { len, _marker: PhantomData, data: buf }
+    }
+
+}
+
+///
+/// Storage wrapper that aligns its buffer to the alignment of `A`
+///
+/// `A` is usually one of [`Align16`], [`Align32`], [`Align64`] or
+/// [`Align128`], but any type works. It is only used for its alignment and
+/// is never stored. This is useful for DMA buffers and SIMD loads that need
+/// more alignment than the items themselves have. See [`AlignedStack`].
+///
+#[repr(C)]
+pub struct Aligned<A, S:?Sized> {
+    _align: [A; 0],
+    storage: S
+}
+
+impl<A, S:?Sized> sealed::Sealed for Aligned<A,S> {}
+
+//the buffer is at the start, since the alignment field takes no space
+impl<T, A, S:?Sized+Storage<T>> Storage<T> for Aligned<A,S> {
+    const KIND: BufferKind = S::KIND;
+    fn as_uninit(&self) -> &[MaybeUninit<T>] { self.storage.as_uninit() }
+    fn as_uninit_mut(&mut self) -> &mut [MaybeUninit<T>] { self.storage.as_uninit_mut() }
+}
+
+macro_rules! align_types {
+    ($($name:ident = $align:literal),*) => {$(
+        #[doc = concat!("Marker type with an alignment of ", stringify!($align), " bytes. See [`Aligned`]")]
+        #[repr(align($align))]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name;
+    )*};
+}
+
+align_types!(Align16 = 16, Align32 = 32, Align64 = 64, Align128 = 128);
+
+///
+/// A [`Stack`](crate::Stack) whose buffer is aligned to the alignment of `A`
+///
+/// # Examples
+/// ```
+/// # use stack_stack::{AlignedStack, Align64};
+/// let mut s: AlignedStack<f32, 16, Align64> = AlignedStack::new();
+/// s.extend_from_slice(&[1.0, 2.0, 3.0]).ok();
+///
+/// assert_eq!(s.as_ptr() as usize % 64, 0);
+/// assert_eq!(s, [1.0, 2.0, 3.0]);
+/// ```
+///
+pub type AlignedStack<T, const N:usize, A, L = usize> = StackBase<T, Aligned<A, [MaybeUninit<T>; N]>, L>;
+
+impl<T, const N:usize, A, L:LenType> StackBase<T, Aligned<A, [MaybeUninit<T>; N]>, L> {
+
+    /// Creates an empty stack
+    pub const fn new() -> Self {
+        let () = crate::Stack::<T,N,L>::LEN_FITS;
+        StackBase {
+            len: L::ZERO,
+            _marker: PhantomData,
+            data: Aligned { _align: [], storage: [const { MaybeUninit::uninit() }; N] }
+        }
+    }
+
+}
+
+impl<T, const N:usize, A, L:LenType> Default for StackBase<T, Aligned<A, [MaybeUninit<T>; N]>, L> {
+    fn default() -> Self { Self::new() }
+}
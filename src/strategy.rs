@@ -0,0 +1,44 @@
+//!
+//! [`proptest`] strategies for generating stacks
+//!
+//! Enabled with the `proptest` feature.
+//!
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::Stack;
+
+///
+/// Creates a strategy for stacks of capacity `N` with values from `element`
+///
+/// Besides lengths anywhere from `0` to `N`, the edge cases of an empty
+/// stack, a full stack and a stack with one slot left are generated more
+/// often than they would be by chance.
+///
+/// # Examples
+/// ```
+/// # use stack_stack::Stack;
+/// use proptest::prelude::*;
+/// use stack_stack::strategy::stack;
+///
+/// proptest!(|(mut s in stack::<_, 8>(any::<u8>()))| {
+///     let len = s.len();
+///     prop_assert!(len <= 8);
+///     prop_assert_eq!(s.push(0).is_ok(), len < 8);
+/// });
+/// ```
+///
+pub fn stack<S:Strategy+Clone, const N:usize>(element: S) -> impl Strategy<Value = Stack<S::Value,N>> {
+    let len = prop_oneof![
+        1 => Just(0),
+        1 => Just(N),
+        1 => Just(N.saturating_sub(1)),
+        4 => 0..=N
+    ];
+    len.prop_flat_map(move |len| vec(element.clone(), len)).prop_map(|values| {
+        let mut stack = Stack::new();
+        stack.extend(values);
+        stack
+    })
+}
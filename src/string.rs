@@ -0,0 +1,214 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{self, Arguments, Debug, Display, Formatter, Result as FmtResult};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::str::from_utf8_unchecked;
+
+use crate::Stack;
+
+///
+/// A UTF-8 string stored in a [`Stack`] of `N` bytes
+///
+/// Adding text that doesn't fit adds as much of it as fits, cut at a char
+/// boundary, and returns the rest. It implements [`fmt::Write`], so it can
+/// be written to with `write!()`, but it is usually created with
+/// [`stack_format!`](crate::stack_format).
+///
+/// # Examples
+/// ```
+/// # use stack_stack::StackString;
+/// let mut s: StackString<8> = StackString::new();
+/// assert_eq!(s.push_str("hello"), Ok(()));
+/// assert_eq!(s.push('!'), Ok(()));
+/// assert_eq!(s.push_str(" wörld"), Err("örld"));
+///
+/// assert_eq!(s, "hello! w");
+/// assert_eq!(s.pop(), Some('w'));
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct StackString<const N:usize> {
+    buf: Stack<u8,N>
+}
+
+impl<const N:usize> StackString<N> {
+
+    /// Creates an empty string
+    pub const fn new() -> Self {
+        Self { buf: Stack::new() }
+    }
+
+    ///
+    /// Formats the arguments into a new string
+    ///
+    /// Returns an error if the formatted text doesn't fit. Usually called
+    /// through [`try_stack_format!`](crate::try_stack_format).
+    ///
+    pub fn from_fmt(args: Arguments<'_>) -> Result<Self,fmt::Error> {
+        let mut s = Self::new();
+        fmt::Write::write_fmt(&mut s, args)?;
+        Ok(s)
+    }
+
+    ///
+    /// Formats the arguments into a new string, cutting it short if it
+    /// doesn't fit
+    ///
+    /// When the text is cut short, it ends with `marker`, such as `"…"`,
+    /// which may be empty. Usually called through
+    /// [`stack_format!`](crate::stack_format).
+    ///
+    pub fn from_fmt_truncated(args: Arguments<'_>, marker: &str) -> Self {
+        let mut s = Self::new();
+        if fmt::Write::write_fmt(&mut s, args).is_err() {
+            let mut len = N.saturating_sub(marker.len());
+            while !s.is_char_boundary(len) {
+                len -= 1;
+            }
+            s.truncate(len);
+            s.push_str(marker).ok();
+        }
+        s
+    }
+
+    /// The quantity of bytes in the string
+    pub fn len(&self) -> usize { self.buf.len() }
+
+    /// `true` when the string is empty
+    pub fn is_empty(&self) -> bool { self.buf.is_empty() }
+
+    /// The most bytes the string can hold
+    pub fn capacity(&self) -> usize { N }
+
+    /// Returns the contents as a `str`
+    pub fn as_str(&self) -> &str {
+        unsafe { from_utf8_unchecked(&self.buf) }
+    }
+
+    /// Returns the underlying bytes
+    pub fn into_bytes(self) -> Stack<u8,N> { self.buf }
+
+    ///
+    /// Adds a char to the end of the string
+    ///
+    /// If the string is full, the char is returned in an `Err()`
+    ///
+    pub fn push(&mut self, c: char) -> Result<(),char> {
+        let mut bytes = [0; 4];
+        self.push_str(c.encode_utf8(&mut bytes)).map_err(|_| c)
+    }
+
+    ///
+    /// Adds as much of a `str` as fits to the end of the string
+    ///
+    /// If it didn't all fit, the rest is returned in an `Err()`
+    ///
+    pub fn push_str<'a>(&mut self, s: &'a str) -> Result<(),&'a str> {
+        let mut n = s.len().min(N - self.len());
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        let (head, tail) = s.split_at(n);
+        self.buf.extend_from_slice(head.as_bytes()).ok();
+        if tail.is_empty() { Ok(()) } else { Err(tail) }
+    }
+
+    /// Removes the last char and returns it
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.buf.truncate(self.len() - c.len_utf8());
+        Some(c)
+    }
+
+    ///
+    /// Shortens the string to `len` bytes, doing nothing if it is already shorter
+    ///
+    /// # Panics
+    /// Panics if `len` isn't on a char boundary
+    ///
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            if !self.is_char_boundary(len) {
+                panic!("Attempted to truncate at {len}, which isn't a char boundary");
+            }
+            self.buf.truncate(len);
+        }
+    }
+
+    /// Removes all of the text
+    pub fn clear(&mut self) { self.buf.clear() }
+
+}
+
+impl<const N:usize> Deref for StackString<N> {
+    type Target = str;
+    fn deref(&self) -> &str { self.as_str() }
+}
+
+impl<const N:usize> AsRef<str> for StackString<N> {
+    fn as_ref(&self) -> &str { self.as_str() }
+}
+
+impl<const N:usize> AsRef<[u8]> for StackString<N> {
+    fn as_ref(&self) -> &[u8] { self.as_bytes() }
+}
+
+impl<const N:usize> Borrow<str> for StackString<N> {
+    fn borrow(&self) -> &str { self.as_str() }
+}
+
+impl<'a, const N:usize> TryFrom<&'a str> for StackString<N> {
+    type Error = &'a str;
+    fn try_from(s: &'a str) -> Result<Self,&'a str> {
+        let mut string = Self::new();
+        match string.push_str(s) {
+            Ok(()) => Ok(string),
+            Err(_) => Err(s)
+        }
+    }
+}
+
+impl<const N:usize> fmt::Write for StackString<N> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N:usize> Display for StackString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N:usize> Debug for StackString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N:usize> Eq for StackString<N> {}
+
+impl<const N:usize, const M:usize> PartialEq<StackString<M>> for StackString<N> {
+    fn eq(&self, other: &StackString<M>) -> bool { self.as_str() == other.as_str() }
+}
+
+impl<const N:usize> PartialEq<str> for StackString<N> {
+    fn eq(&self, other: &str) -> bool { self.as_str() == other }
+}
+
+impl<const N:usize> PartialEq<&str> for StackString<N> {
+    fn eq(&self, other: &&str) -> bool { self.as_str() == *other }
+}
+
+impl<const N:usize> PartialOrd for StackString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<const N:usize> Ord for StackString<N> {
+    fn cmp(&self, other: &Self) -> Ordering { self.as_str().cmp(other.as_str()) }
+}
+
+impl<const N:usize> Hash for StackString<N> {
+    fn hash<H:Hasher>(&self, state: &mut H) { self.as_str().hash(state) }
+}